@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+use breakout::{sweep_circle_aabb, Aabb, SpatialGrid};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const BLOCK_SIZE: Vec2 = Vec2::new(60.0, 15.0);
+const BALL_RADIUS: f32 = 5.0;
+
+fn blocks(count: u32) -> Vec<(Entity, Aabb)> {
+    let columns = (count as f32).sqrt().ceil() as u32;
+    (0..count)
+        .map(|index| {
+            let position = Vec2::new(
+                (index % columns) as f32 * (BLOCK_SIZE.x + 10.0),
+                (index / columns) as f32 * (BLOCK_SIZE.y + 5.0),
+            );
+            (Entity::from_raw(index), Aabb::new(position, BLOCK_SIZE))
+        })
+        .collect()
+}
+
+fn balls() -> Vec<(Vec2, Vec2)> {
+    (0..16)
+        .map(|index| {
+            let position = Vec2::new(index as f32 * 397.0, index as f32 * 113.0);
+            let motion = Vec2::from_angle(index as f32).rotate(Vec2::X) * 12.0;
+            (position, motion)
+        })
+        .collect()
+}
+
+fn brute_force(blocks: &[(Entity, Aabb)], balls: &[(Vec2, Vec2)]) -> usize {
+    balls
+        .iter()
+        .map(|(position, motion)| {
+            blocks
+                .iter()
+                .filter(|(_, aabb)| {
+                    sweep_circle_aabb(*position, *motion, BALL_RADIUS, *aabb).is_some()
+                })
+                .count()
+        })
+        .sum()
+}
+
+fn spatial_grid(grid: &SpatialGrid, balls: &[(Vec2, Vec2)]) -> usize {
+    balls
+        .iter()
+        .map(|(position, motion)| {
+            let reach = Aabb {
+                center: *position,
+                half_extents: Vec2::splat(motion.length() + BALL_RADIUS),
+            };
+            grid.query(reach)
+                .iter()
+                .filter(|(_, aabb)| {
+                    sweep_circle_aabb(*position, *motion, BALL_RADIUS, *aabb).is_some()
+                })
+                .count()
+        })
+        .sum()
+}
+
+fn broad_phase(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("broad_phase");
+    let balls = balls();
+
+    for count in [100, 1_000, 10_000] {
+        let blocks = blocks(count);
+        let mut grid = SpatialGrid::new(64.0);
+        for (entity, aabb) in &blocks {
+            grid.insert(*entity, *aabb);
+        }
+
+        group.bench_with_input(
+            BenchmarkId::new("brute_force", count),
+            &blocks,
+            |b, blocks| b.iter(|| brute_force(black_box(blocks), black_box(&balls))),
+        );
+        group.bench_with_input(BenchmarkId::new("spatial_grid", count), &grid, |b, grid| {
+            b.iter(|| spatial_grid(black_box(grid), black_box(&balls)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, broad_phase);
+criterion_main!(benches);
@@ -1,18 +1,32 @@
 use bevy::{
+    ecs::system::SystemParam,
     prelude::*,
-    sprite::{
-        collide_aabb::{collide, Collision},
-        MaterialMesh2dBundle,
-    },
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
 };
 
 use crate::{
-    config::{Config, GameConfig},
+    block::{Block, GridPosition},
+    catch::Caught,
+    collision::{
+        contact_circle_aabb, contact_circle_inside_aabb, sweep_circle_aabb,
+        sweep_circle_inside_aabb, Aabb, Contact, Sweep,
+    },
+    config::{BallConfig, Config, GameConfig, SpeedProgression},
     debug::MousePosition,
-    game::{AppState, BoundingBox, PlayState, SpawningSet},
+    game::{
+        despawn_with_component, not_paused, AppState, BoundingBox, PlayState, SimulationSet,
+        SpawningSet,
+    },
+    gamepad::GamepadInput,
+    input::Action,
     paddle::{Dimensions, Paddle, Speed},
+    powerup::PowerUpSet,
+    spatial::SpatialGrid,
 };
 
+/// Upper bound on the bounces resolved for a single ball within one frame.
+const MAX_SUBSTEPS: usize = 8;
+
 #[derive(Component)]
 pub struct Ball {
     pub radius: f32,
@@ -21,11 +35,75 @@ pub struct Ball {
 #[derive(Resource, Debug, Default)]
 pub struct Bounces(pub u32);
 
+/// Speed every ball in play flies at, in units per second, along with what the configured
+/// [`SpeedProgression`] needs to remember to speed it up.
+#[derive(Resource, Debug)]
+pub struct BallSpeed {
+    pub current: f32,
+    blocks_hit: u32,
+    rows_reached: Vec<u32>,
+}
+
+impl BallSpeed {
+    pub fn new(base_speed: f32) -> Self {
+        Self {
+            current: base_speed,
+            blocks_hit: 0,
+            rows_reached: Vec::new(),
+        }
+    }
+
+    /// Advances the speed model by a single bounce. `block_row` is the row of the block the ball
+    /// bounced off, or `None` when it bounced off anything else.
+    fn register_bounce(&mut self, config: &BallConfig, block_row: Option<u32>) {
+        let increase = match &config.speed_progression {
+            SpeedProgression::PerBounce { percent } => self.current * percent / 100.0,
+            SpeedProgression::PerBlocks { blocks, step } => match block_row {
+                Some(_) => {
+                    self.blocks_hit += 1;
+                    if self.blocks_hit % blocks.get() == 0 {
+                        *step
+                    } else {
+                        0.0
+                    }
+                }
+                None => 0.0,
+            },
+            SpeedProgression::Rows { rows, step } => match block_row {
+                Some(row) if rows.contains(&row) && !self.rows_reached.contains(&row) => {
+                    self.rows_reached.push(row);
+                    *step
+                }
+                _ => 0.0,
+            },
+        };
+
+        self.current = (self.current + increase).min(config.max_speed);
+    }
+}
+
 #[derive(Event, Debug)]
 pub struct BallCollisionEvent {
     pub ball: Entity,
     pub with: Entity,
-    pub collision: Collision,
+    /// Point on the surface of `with` that the ball touched.
+    pub contact: Vec2,
+    /// Normal of the touched surface, pointing back towards the ball.
+    pub normal: Vec2,
+    /// How deep the ball was inside of `with` before it got pushed back out.
+    pub depth: f32,
+}
+
+/// Sent when the only ball left in play touches the bottom of the playfield, which costs a life.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LastBallLost;
+
+/// Splits `ball` into `count` balls flying in directions fanned out around its current one. Sent
+/// while the ball is being aimed, the split waits for the launch.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SplitBallEvent {
+    pub ball: Entity,
+    pub count: u32,
 }
 
 pub struct BallPlugin;
@@ -33,37 +111,65 @@ pub struct BallPlugin;
 impl Plugin for BallPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<BallCollisionEvent>()
+            .add_event::<SplitBallEvent>()
+            .add_event::<LastBallLost>()
             .insert_resource(Bounces::default())
             .add_systems(
                 OnEnter(AppState::Playing),
                 spawn_ball.in_set(SpawningSet::Ball),
             )
+            .add_systems(
+                OnExit(AppState::Playing),
+                (despawn_with_component::<Ball>, reset_play_state),
+            )
             .add_systems(
                 Update,
-                (follow_paddle, play_ball, calculate_ball_direction).distributive_run_if(
+                (play_ball, calculate_ball_direction).distributive_run_if(
+                    in_state(AppState::Playing)
+                        .and_then(in_state(PlayState::ReadyToShoot))
+                        .and_then(not_paused),
+                ),
+            )
+            .add_systems(
+                FixedUpdate,
+                follow_paddle.in_set(SimulationSet::Physics).run_if(
                     in_state(AppState::Playing).and_then(in_state(PlayState::ReadyToShoot)),
                 ),
             )
             .add_systems(
-                Update,
+                FixedUpdate,
                 (
-                    (detect_collision, change_ball_direction, move_ball).chain(),
-                    increase_ball_speed.run_if(resource_changed::<Bounces>()),
-                    ball_touched_bottom,
+                    move_ball.in_set(SimulationSet::Physics),
+                    (count_bounces, increase_ball_speed, ball_touched_bottom)
+                        .in_set(SimulationSet::Gameplay),
                 )
                     .distributive_run_if(
                         in_state(AppState::Playing).and_then(in_state(PlayState::BallInGame)),
                     ),
             )
+            .add_systems(
+                FixedUpdate,
+                split_balls
+                    .in_set(SimulationSet::Gameplay)
+                    .after(PowerUpSet)
+                    .run_if(in_state(AppState::Playing)),
+            )
+            // A caught ball keeps its speed, as no life was lost.
             .add_systems(
                 OnExit(PlayState::BallInGame),
-                (follow_paddle, reset_speed_on_new_life),
+                (
+                    follow_paddle,
+                    reset_speed_on_new_life.run_if(not(any_with_component::<Caught>())),
+                ),
             );
     }
 }
 
 fn increase_ball_speed(
+    mut reader: EventReader<BallCollisionEvent>,
     mut balls: Query<&mut Speed, With<Ball>>,
+    blocks: Query<&GridPosition, With<Block>>,
+    mut ball_speed: ResMut<BallSpeed>,
     game_config: Res<GameConfig>,
     assets: Res<Assets<Config>>,
 ) {
@@ -71,13 +177,41 @@ fn increase_ball_speed(
         panic!("game config could not be loaded")
     };
 
+    let mut bounced = false;
+    for event in reader.iter() {
+        let block_row = blocks.get(event.with).ok().map(|position| position.row);
+        ball_speed.register_bounce(&config.ball, block_row);
+        bounced = true;
+    }
+
+    if !bounced {
+        return;
+    }
+
     for mut speed in &mut balls {
-        speed.0.x += speed.0.x * (config.ball.speed_increase / 100.0);
-        speed.0.y += speed.0.y * (config.ball.speed_increase / 100.0);
+        speed.0 = constrain_velocity(
+            speed.0,
+            ball_speed.current,
+            config.ball.min_vertical_component,
+        );
     }
 }
 
-fn reset_speed_on_new_life(
+/// Scales `velocity` to `speed`, tilting it towards the vertical if less than
+/// `min_vertical_component` of it points up or down, so the ball never ends up crawling sideways.
+pub fn constrain_velocity(velocity: Vec2, speed: f32, min_vertical_component: f32) -> Vec2 {
+    let direction = velocity.try_normalize().unwrap_or(Vec2::Y);
+    let vertical = direction.y.abs().max(min_vertical_component).min(1.0);
+    let horizontal = (1.0 - vertical * vertical).sqrt();
+
+    Vec2::new(
+        horizontal.copysign(direction.x),
+        vertical.copysign(direction.y),
+    ) * speed
+}
+
+pub fn reset_speed_on_new_life(
+    mut commands: Commands,
     mut balls: Query<&mut Speed, With<Ball>>,
     game_config: Res<GameConfig>,
     assets: Res<Assets<Config>>,
@@ -86,74 +220,244 @@ fn reset_speed_on_new_life(
         panic!("game config could not be loaded")
     };
 
+    commands.insert_resource(BallSpeed::new(config.ball.base_speed));
     for mut speed in &mut balls {
-        speed.0 = Vec2::new(config.ball.initial_speed, 150.0);
+        speed.0 = Vec2::Y * config.ball.base_speed;
     }
 }
 
-fn change_ball_direction(
-    mut balls: Query<&mut Speed, With<Ball>>,
+fn count_bounces(
+    balls: Query<With<Ball>>,
     mut reader: EventReader<BallCollisionEvent>,
     mut bounces: ResMut<Bounces>,
 ) {
     for event in reader.iter() {
-        if let Ok(mut speed) = balls.get_mut(event.ball) {
-            match event.collision {
-                Collision::Left | Collision::Right => {
-                    speed.0.x *= -1.0;
-                    bounces.0 += 1;
-                }
-                Collision::Top | Collision::Bottom => {
-                    speed.0.y *= -1.0;
-                    bounces.0 += 1;
-                }
-                Collision::Inside => {}
-            }
+        if balls.contains(event.ball) {
+            bounces.0 += 1;
+        }
+    }
+}
+
+/// Something the ball can bounce off, captured at the beginning of a frame.
+struct Collider {
+    entity: Entity,
+    aabb: Aabb,
+    kind: ColliderKind,
+}
+
+enum ColliderKind {
+    Solid,
+    /// The ball is kept inside of the collider instead of outside of it.
+    Container,
+    Paddle {
+        max_deflection_angle: f32,
+        /// Horizontal speed the paddle adds to the ball.
+        english: f32,
+    },
+}
+
+impl Collider {
+    fn sweep(&self, start: Vec2, motion: Vec2, radius: f32) -> Option<Sweep> {
+        match self.kind {
+            ColliderKind::Container => sweep_circle_inside_aabb(start, motion, radius, self.aabb),
+            _ => sweep_circle_aabb(start, motion, radius, self.aabb),
+        }
+    }
+
+    fn contact(&self, center: Vec2, radius: f32) -> Option<Contact> {
+        match self.kind {
+            ColliderKind::Container => contact_circle_inside_aabb(center, radius, self.aabb),
+            _ => contact_circle_aabb(center, radius, self.aabb),
+        }
+    }
+}
+
+fn change_ball_direction(
+    velocity: Vec2,
+    position: Vec2,
+    normal: Vec2,
+    collider: &Collider,
+) -> Vec2 {
+    match collider.kind {
+        ColliderKind::Paddle {
+            max_deflection_angle,
+            english,
+        } if normal.y > 0.0 => paddle_bounce(
+            velocity,
+            position.x,
+            collider.aabb,
+            max_deflection_angle,
+            english,
+        ),
+        _ => velocity - 2.0 * velocity.dot(normal) * normal,
+    }
+}
+
+/// Sends the ball back up at an angle that depends on how far from the paddle's center it
+/// landed, reaching `max_deflection_angle` from the vertical at the very edges. The moving
+/// paddle then drags the ball along by `english`, without tilting it past `max_deflection_angle`.
+/// The ball keeps its speed.
+pub fn paddle_bounce(
+    velocity: Vec2,
+    hit_x: f32,
+    paddle: Aabb,
+    max_deflection_angle: f32,
+    english: f32,
+) -> Vec2 {
+    let speed = velocity.length();
+    let offset = ((hit_x - paddle.center.x) / paddle.half_extents.x).clamp(-1.0, 1.0);
+    let angle = offset * max_deflection_angle;
+    let aimed = Vec2::new(angle.sin(), angle.cos()) * speed;
+
+    let dragged = aimed + Vec2::X * english;
+    let angle = dragged
+        .x
+        .atan2(dragged.y)
+        .clamp(-max_deflection_angle, max_deflection_angle);
+    Vec2::new(angle.sin(), angle.cos()) * speed
+}
+
+/// Pushes the ball out of every collider it overlaps with. Only overlaps the ball was still
+/// moving into bounce it and count as contacts, so a ball that is already on its way out does
+/// not get flipped a second time.
+fn resolve_overlaps(
+    position: &mut Vec2,
+    velocity: &mut Vec2,
+    radius: f32,
+    colliders: &[Collider],
+) -> Vec<(Entity, Contact)> {
+    let mut contacts = Vec::new();
+
+    for collider in colliders {
+        let Some(contact) = collider.contact(*position, radius) else {
+            continue;
+        };
+
+        *position += contact.normal * contact.depth;
+        if velocity.dot(contact.normal) < 0.0 {
+            *velocity = change_ball_direction(*velocity, *position, contact.normal, collider);
+            contacts.push((collider.entity, contact));
         }
     }
+
+    contacts
 }
 
-fn detect_collision(
-    balls: Query<(&Ball, &GlobalTransform, Entity), With<Ball>>,
-    bouncable: Query<(&GlobalTransform, &Dimensions, Entity)>,
+/// Moves the ball by `distance` along its velocity, bouncing it off the earliest collider hit
+/// along the way and continuing with the rest of the distance, up to [`MAX_SUBSTEPS`] times.
+///
+/// Returns every contact in the order they happened.
+fn advance_ball(
+    position: &mut Vec2,
+    velocity: &mut Vec2,
+    radius: f32,
+    distance: f32,
+    colliders: &[Collider],
+) -> Vec<(Entity, Contact)> {
+    let mut contacts = resolve_overlaps(position, velocity, radius, colliders);
+    let mut motion = velocity.normalize_or_zero() * distance;
+
+    for _ in 0..MAX_SUBSTEPS {
+        let earliest = colliders
+            .iter()
+            .filter_map(|collider| {
+                collider
+                    .sweep(*position, motion, radius)
+                    .map(|sweep| (collider, sweep))
+            })
+            .min_by(|(_, a), (_, b)| a.time.total_cmp(&b.time));
+
+        let Some((collider, sweep)) = earliest else {
+            *position += motion;
+            break;
+        };
+
+        *position += motion * sweep.time;
+        let remaining = motion.length() * (1.0 - sweep.time);
+        *velocity = change_ball_direction(*velocity, *position, sweep.normal, collider);
+        motion = velocity.normalize_or_zero() * remaining;
+        contacts.push((
+            collider.entity,
+            Contact {
+                point: *position - sweep.normal * radius,
+                normal: sweep.normal,
+                depth: 0.0,
+            },
+        ));
+    }
+
+    contacts
+}
+
+fn move_ball(
+    mut balls: Query<(Entity, &Ball, &mut Transform, &mut Speed)>,
+    bouncable: Query<(&GlobalTransform, &Dimensions)>,
+    paddles: Query<(&Paddle, Option<&Speed>), Without<Ball>>,
+    bounding_box: Query<Entity, With<BoundingBox>>,
+    grid: Res<SpatialGrid>,
     mut writer: EventWriter<BallCollisionEvent>,
+    time: Res<FixedTime>,
 ) {
-    for (paddle_transform, dimensions, bouncable_entity) in &bouncable {
-        for (ball, ball_transform, ball_entity) in &balls {
-            if let Some(collision) = collide(
-                ball_transform.translation(),
-                Vec2::splat(ball.radius),
-                paddle_transform.translation(),
-                dimensions.0,
-            ) {
-                match collision {
-                    Collision::Left => writer.send(BallCollisionEvent {
-                        ball: ball_entity,
-                        with: bouncable_entity,
-                        collision: Collision::Left,
-                    }),
-                    Collision::Right => writer.send(BallCollisionEvent {
-                        ball: ball_entity,
-                        with: bouncable_entity,
-                        collision: Collision::Right,
-                    }),
-                    Collision::Top => writer.send(BallCollisionEvent {
-                        ball: ball_entity,
-                        with: bouncable_entity,
-                        collision: Collision::Top,
-                    }),
-                    Collision::Bottom => writer.send(BallCollisionEvent {
-                        ball: ball_entity,
-                        with: bouncable_entity,
-                        collision: Collision::Bottom,
-                    }),
-                    Collision::Inside => {}
+    let collider = |entity: Entity| {
+        let (transform, dimensions) = bouncable.get(entity).ok()?;
+        Some(Collider {
+            entity,
+            aabb: Aabb::new(transform.translation().truncate(), dimensions.0),
+            kind: if let Ok((paddle, speed)) = paddles.get(entity) {
+                ColliderKind::Paddle {
+                    max_deflection_angle: paddle.max_deflection_angle,
+                    english: paddle.english * speed.map_or(0.0, |speed| speed.0.x),
                 }
-            }
+            } else if bounding_box.contains(entity) {
+                ColliderKind::Container
+            } else {
+                ColliderKind::Solid
+            },
+        })
+    };
+
+    for (ball_entity, ball, mut transform, mut speed) in &mut balls {
+        let mut position = transform.translation.truncate();
+        let distance = speed.0.length() * time.period.as_secs_f32();
+
+        // However the ball bounces, it can't get further than `distance` away from where it is.
+        let reach = Aabb {
+            center: position,
+            half_extents: Vec2::splat(distance + ball.radius),
+        };
+        let colliders: Vec<_> = bounding_box
+            .iter()
+            .chain(grid.query(reach).into_iter().map(|(entity, _)| entity))
+            .filter_map(collider)
+            .collect();
+
+        let contacts = advance_ball(
+            &mut position,
+            &mut speed.0,
+            ball.radius,
+            distance,
+            &colliders,
+        );
+
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+
+        for (entity, contact) in contacts {
+            writer.send(BallCollisionEvent {
+                ball: ball_entity,
+                with: entity,
+                contact: contact.point,
+                normal: contact.normal,
+                depth: contact.depth,
+            });
         }
     }
 }
 
+fn reset_play_state(mut state: ResMut<NextState<PlayState>>) {
+    state.set(PlayState::ReadyToShoot);
+}
+
 fn spawn_ball(
     mut commands: Commands,
     mut materials: ResMut<Assets<ColorMaterial>>,
@@ -184,80 +488,222 @@ fn spawn_ball(
         Ball {
             radius: config.ball.radius,
         },
-        Speed(Vec2::new(config.ball.initial_speed, 150.0)),
+        Speed(Vec2::Y * config.ball.base_speed),
         Name::from("Ball"),
     ));
-}
-
-fn move_ball(mut ball: Query<(&mut Transform, &Speed), With<Ball>>, time: Res<Time>) {
-    for (mut transform, speed) in &mut ball {
-        transform.translation.y += speed.0.y * time.delta_seconds() * 150.0;
-        transform.translation.x -= speed.0.x * time.delta_seconds() * 150.0;
-    }
+    commands.insert_resource(BallSpeed::new(config.ball.base_speed));
 }
 
 fn follow_paddle(
     game_config: Res<GameConfig>,
     assets: Res<Assets<Config>>,
     paddle: Query<&GlobalTransform, With<Paddle>>,
-    mut ball: Query<&mut Transform, With<Ball>>,
+    mut balls: Query<(&mut Transform, Option<&Caught>), With<Ball>>,
 ) {
     let Some(config) = assets.get(&game_config.config) else {
         panic!("game config could not be loaded")
     };
 
-    let paddle = paddle.single();
-    let mut ball = ball.single_mut();
+    let Ok(paddle) = paddle.get_single() else {
+        return;
+    };
 
-    ball.translation.x = paddle.translation().x;
-    ball.translation.y = paddle.translation().y + config.ball.offset_from_paddle;
+    for (mut ball, caught) in &mut balls {
+        ball.translation.x = paddle.translation().x + caught.map_or(0.0, |caught| caught.offset);
+        ball.translation.y = paddle.translation().y + config.ball.offset_from_paddle;
+    }
 }
 
-fn play_ball(input: Res<Input<MouseButton>>, mut state: ResMut<NextState<PlayState>>) {
-    if input.just_pressed(MouseButton::Left) {
+fn play_ball(actions: Res<Input<Action>>, mut state: ResMut<NextState<PlayState>>) {
+    if actions.just_pressed(Action::Launch) {
         state.set(PlayState::BallInGame)
     }
 }
 
-fn ball_touched_bottom(
+/// Removes balls that fell through the bottom of the field. The last ball is kept for the next
+/// life instead, and the game goes back to aiming.
+/// Removes balls touching the bottom while others are still in play. Losing the last one sends
+/// [`LastBallLost`] and goes back to aiming.
+pub fn ball_touched_bottom(
+    mut commands: Commands,
     mut reader: EventReader<BallCollisionEvent>,
+    balls: Query<With<Ball>>,
     bounding_box: Query<With<BoundingBox>>,
     mut state: ResMut<NextState<PlayState>>,
+    mut lost: EventWriter<LastBallLost>,
 ) {
+    let mut balls_in_play = balls.iter().count();
+
     for event in reader.iter() {
-        if bounding_box.get(event.with).is_ok() && event.collision == Collision::Bottom {
-            state.set(PlayState::ReadyToShoot)
+        if bounding_box.get(event.with).is_ok() && event.normal == Vec2::Y {
+            if balls_in_play > 1 {
+                commands.entity(event.ball).despawn_recursive();
+                balls_in_play -= 1;
+            } else if balls_in_play == 1 {
+                state.set(PlayState::ReadyToShoot);
+                lost.send(LastBallLost);
+                balls_in_play = 0;
+            }
+        }
+    }
+}
+
+/// Splits balls in play right away, and a ball that is being aimed once it is launched.
+fn split_balls(
+    mut commands: Commands,
+    mut reader: EventReader<SplitBallEvent>,
+    mut held: Local<Vec<SplitBallEvent>>,
+    play_state: Res<State<PlayState>>,
+    mut balls: Query<(
+        &Ball,
+        &mut Speed,
+        &Transform,
+        &Mesh2dHandle,
+        &Handle<ColorMaterial>,
+    )>,
+    game_config: Res<GameConfig>,
+    assets: Res<Assets<Config>>,
+) {
+    let Some(config) = assets.get(&game_config.config) else {
+        panic!("game config could not be loaded")
+    };
+
+    held.extend(reader.iter().copied());
+    if *play_state.get() != PlayState::BallInGame {
+        return;
+    }
+
+    for event in held.drain(..) {
+        let Ok((ball, mut speed, transform, mesh, material)) = balls.get_mut(event.ball) else {
+            continue;
+        };
+
+        let mut directions =
+            spread_directions(speed.0, event.count, config.ball.split_angle.to_radians());
+        let Some(first) = directions.pop() else {
+            continue;
+        };
+        speed.0 = first;
+
+        for direction in directions {
+            commands.spawn((
+                MaterialMesh2dBundle {
+                    mesh: mesh.clone(),
+                    material: material.clone(),
+                    transform: *transform,
+                    ..Default::default()
+                },
+                Ball {
+                    radius: ball.radius,
+                },
+                Speed(direction),
+                Name::from("Ball"),
+            ));
+        }
+    }
+}
+
+/// Fans `count` copies of `velocity` out evenly around it, `angle` radians apart from each other.
+fn spread_directions(velocity: Vec2, count: u32, angle: f32) -> Vec<Vec2> {
+    let middle = (count as f32 - 1.0) / 2.0;
+
+    (0..count)
+        .map(|i| Vec2::from_angle((i as f32 - middle) * angle).rotate(velocity))
+        .collect()
+}
+
+/// Input device the ball was last aimed with.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+enum AimDevice {
+    /// Neither the stick nor the mouse moved yet.
+    #[default]
+    None,
+    Cursor,
+    /// Direction the right stick pointed in when it was last pushed.
+    Stick(Vec2),
+}
+
+/// Where the player aims the ball, with whichever of the right stick and the mouse moved last.
+/// The stick springs back to the center once let go to press launch, so its direction is kept
+/// until the mouse moves.
+#[derive(SystemParam)]
+struct Aim<'w, 's> {
+    paddle: Query<'w, 's, &'static GlobalTransform, With<Paddle>>,
+    mouse_position: Res<'w, MousePosition>,
+    gamepad: GamepadInput<'w>,
+    device: Local<'s, AimDevice>,
+    last_cursor: Local<'s, Option<Vec2>>,
+}
+
+impl Aim<'_, '_> {
+    fn direction(&mut self) -> Option<Vec2> {
+        let cursor = self.mouse_position.viewport;
+        let cursor_moved = self.last_cursor.is_some_and(|last| last != cursor);
+        *self.last_cursor = Some(cursor);
+
+        if let Some(aim) = self.gamepad.aim() {
+            *self.device = AimDevice::Stick(aim);
+        } else if cursor_moved {
+            *self.device = AimDevice::Cursor;
+        }
+
+        match *self.device {
+            AimDevice::Stick(aim) => Some(aim),
+            AimDevice::None if self.gamepad.is_active() => None,
+            AimDevice::None | AimDevice::Cursor => {
+                Some(self.mouse_position.world - self.paddle.single().translation().truncate())
+            }
         }
     }
 }
 
 fn calculate_ball_direction(
-    paddle: Query<&GlobalTransform, With<Paddle>>,
-    mouse_position: Res<MousePosition>,
-    mut ball: Query<&mut Speed, With<Ball>>,
+    mut aim: Aim,
+    mut balls: Query<&mut Speed, With<Ball>>,
+    ball_speed: Res<BallSpeed>,
+    game_config: Res<GameConfig>,
+    assets: Res<Assets<Config>>,
 ) {
-    let paddle = paddle.single();
-    let paddle_translation = paddle.translation();
-    let mut ball = ball.single_mut();
+    let Some(config) = assets.get(&game_config.config) else {
+        panic!("game config could not be loaded")
+    };
 
-    let mut to_cursor = (mouse_position.world - paddle_translation.truncate()).normalize();
-    to_cursor.x *= -1.0;
+    let Some(direction) = aim.direction() else {
+        return;
+    };
 
-    ball.0 = to_cursor;
+    for mut ball in &mut balls {
+        ball.0 = constrain_velocity(
+            direction,
+            ball_speed.current,
+            config.ball.min_vertical_component,
+        );
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::num::NonZeroU32;
+
+    use crate::{config::insert_test_config, gamepad::ActiveGamepad, spatial::update_spatial_grid};
+
     use super::*;
 
+    fn insert_gamepad(app: &mut App) -> Gamepad {
+        let gamepad = Gamepad::new(0);
+        app.insert_resource(ActiveGamepad(gamepad))
+            .insert_resource(Axis::<GamepadAxis>::default());
+        gamepad
+    }
+
     #[test]
-    fn should_change_state_on_lmb() {
+    fn should_change_state_on_launch() {
         let mut app = App::new();
         app.add_state::<PlayState>();
 
-        let mut input = Input::<MouseButton>::default();
-        input.press(MouseButton::Left);
-        app.insert_resource(input);
+        let mut actions = Input::<Action>::default();
+        actions.press(Action::Launch);
+        app.insert_resource(actions);
 
         app.add_systems(
             Update,
@@ -271,9 +717,392 @@ mod tests {
     }
 
     #[test]
-    fn should_change_play_state_on_ball_touching_bottom() {
+    fn should_not_launch_while_paused() {
+        let mut app = App::new();
+        app.add_state::<PlayState>();
+
+        let mut actions = Input::<Action>::default();
+        actions.press(Action::Launch);
+        let mut time = Time::default();
+        time.pause();
+        app.insert_resource(actions).insert_resource(time);
+
+        app.add_systems(
+            Update,
+            (
+                play_ball.run_if(not_paused),
+                apply_state_transition::<PlayState>,
+            )
+                .chain(),
+        );
+
+        app.update();
+
+        let state = app.world.resource::<State<PlayState>>().get();
+        assert_eq!(*state, PlayState::ReadyToShoot);
+    }
+
+    #[test]
+    fn should_bounce_fast_ball_off_thin_block_instead_of_tunneling() {
         let mut app = App::new();
         app.add_event::<BallCollisionEvent>();
+
+        app.insert_resource(FixedTime::new_from_secs(1.0));
+        app.insert_resource(SpatialGrid::new(64.0));
+
+        let block = app
+            .world
+            .spawn((
+                GlobalTransform::from_xyz(0.0, 100.0, 0.0),
+                Dimensions(Vec2::new(60.0, 15.0)),
+            ))
+            .id();
+        let ball = app
+            .world
+            .spawn((
+                Ball { radius: 5.0 },
+                Transform::default(),
+                Speed(Vec2::new(0.0, 3000.0)),
+            ))
+            .id();
+
+        app.add_systems(Update, (update_spatial_grid, move_ball).chain());
+        app.update();
+
+        let events: Vec<_> = app
+            .world
+            .resource_mut::<Events<BallCollisionEvent>>()
+            .drain()
+            .collect();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].ball, ball);
+        assert_eq!(events[0].with, block);
+        assert_eq!(events[0].normal, Vec2::NEG_Y);
+        assert_eq!(events[0].contact, Vec2::new(0.0, 92.5));
+
+        assert!(app.world.get::<Speed>(ball).unwrap().0.y < 0.0);
+        assert!(app.world.get::<Transform>(ball).unwrap().translation.y < 100.0 - 7.5);
+    }
+
+    #[test]
+    fn should_resolve_every_bounce_of_a_fast_ball_within_one_step() {
+        let colliders = [
+            Collider {
+                entity: Entity::from_raw(0),
+                aabb: Aabb::new(Vec2::new(0.0, 50.0), Vec2::new(100.0, 2.0)),
+                kind: ColliderKind::Solid,
+            },
+            Collider {
+                entity: Entity::from_raw(1),
+                aabb: Aabb::new(Vec2::new(0.0, -50.0), Vec2::new(100.0, 2.0)),
+                kind: ColliderKind::Solid,
+            },
+        ];
+
+        let mut position = Vec2::ZERO;
+        let mut velocity = Vec2::new(0.0, 1.0);
+        let contacts = advance_ball(&mut position, &mut velocity, 5.0, 1000.0, &colliders);
+
+        let hits: Vec<_> = contacts.iter().map(|(entity, _)| entity.index()).collect();
+        assert_eq!(hits, [0, 1, 0, 1, 0, 1, 0, 1]);
+        assert!(position.y.abs() < 50.0);
+    }
+
+    #[test]
+    fn should_push_ball_out_without_flipping_it_twice() {
+        let paddle = Collider {
+            entity: Entity::from_raw(0),
+            aabb: Aabb::new(Vec2::ZERO, Vec2::new(150.0, 15.0)),
+            kind: ColliderKind::Solid,
+        };
+
+        let mut position = Vec2::new(10.0, 9.0);
+        let mut velocity = Vec2::new(0.0, -1.0);
+        let colliders = [paddle];
+        let contacts = resolve_overlaps(&mut position, &mut velocity, 5.0, &colliders);
+
+        assert_eq!(contacts.len(), 1);
+        assert_eq!(contacts[0].1.normal, Vec2::Y);
+        assert!((contacts[0].1.depth - 3.5).abs() < 1e-6);
+        assert_eq!(position, Vec2::new(10.0, 12.5));
+        assert_eq!(velocity, Vec2::new(0.0, 1.0));
+
+        position.y = 9.0;
+        let contacts = resolve_overlaps(&mut position, &mut velocity, 5.0, &colliders);
+
+        assert!(contacts.is_empty());
+        assert_eq!(position, Vec2::new(10.0, 12.5));
+        assert_eq!(velocity, Vec2::new(0.0, 1.0));
+    }
+
+    #[test]
+    fn should_send_ball_straight_up_when_it_lands_on_paddle_center() {
+        let paddle = Aabb::new(Vec2::new(100.0, -280.0), Vec2::new(150.0, 15.0));
+
+        let velocity = paddle_bounce(
+            Vec2::new(3.0, -4.0),
+            100.0,
+            paddle,
+            60_f32.to_radians(),
+            0.0,
+        );
+
+        assert!(velocity.x.abs() < 1e-5);
+        assert!((velocity.y - 5.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn should_deflect_ball_by_max_angle_on_paddle_edges() {
+        let paddle = Aabb::new(Vec2::new(100.0, -280.0), Vec2::new(150.0, 15.0));
+        let max_angle = 60_f32.to_radians();
+
+        let right = paddle_bounce(Vec2::new(0.0, -5.0), 175.0, paddle, max_angle, 0.0);
+        let left = paddle_bounce(Vec2::new(0.0, -5.0), 20.0, paddle, max_angle, 0.0);
+
+        assert!((right.angle_between(Vec2::Y) - max_angle).abs() < 1e-5);
+        assert!((left.angle_between(Vec2::Y) + max_angle).abs() < 1e-5);
+        assert!((right.length() - 5.0).abs() < 1e-5);
+        assert!((left.length() - 5.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn should_deflect_ball_proportionally_to_off_center_hit() {
+        let paddle = Aabb::new(Vec2::new(100.0, -280.0), Vec2::new(150.0, 15.0));
+        let max_angle = 60_f32.to_radians();
+
+        let velocity = paddle_bounce(Vec2::new(-2.0, -2.0), 137.5, paddle, max_angle, 0.0);
+
+        assert!((Vec2::Y.angle_between(velocity) + 30_f32.to_radians()).abs() < 1e-5);
+        assert!(velocity.x > 0.0);
+        assert!((velocity.length() - 8_f32.sqrt()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn should_curve_ball_in_direction_paddle_moves() {
+        let paddle = Aabb::new(Vec2::ZERO, Vec2::new(150.0, 15.0));
+        let max_angle = 60_f32.to_radians();
+
+        let velocity = paddle_bounce(Vec2::new(0.0, -300.0), 0.0, paddle, max_angle, 300.0);
+
+        assert!((Vec2::Y.angle_between(velocity) + 45_f32.to_radians()).abs() < 1e-5);
+        assert!((velocity.length() - 300.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn should_not_curve_ball_past_max_deflection_angle() {
+        let paddle = Aabb::new(Vec2::ZERO, Vec2::new(150.0, 15.0));
+        let max_angle = 60_f32.to_radians();
+
+        let velocity = paddle_bounce(Vec2::new(0.0, -300.0), -75.0, paddle, max_angle, -5000.0);
+
+        assert!((Vec2::Y.angle_between(velocity) - max_angle).abs() < 1e-5);
+        assert!(velocity.x < 0.0);
+    }
+
+    #[test]
+    fn should_only_aim_ball_bouncing_off_top_of_paddle() {
+        let paddle = Collider {
+            entity: Entity::from_raw(0),
+            aabb: Aabb::new(Vec2::ZERO, Vec2::new(150.0, 15.0)),
+            kind: ColliderKind::Paddle {
+                max_deflection_angle: 60_f32.to_radians(),
+                english: 0.0,
+            },
+        };
+
+        let side = change_ball_direction(
+            Vec2::new(-1.0, -1.0),
+            Vec2::new(80.0, 0.0),
+            Vec2::X,
+            &paddle,
+        );
+        let top =
+            change_ball_direction(Vec2::new(0.0, -1.0), Vec2::new(0.0, 12.5), Vec2::Y, &paddle);
+
+        assert_eq!(side, Vec2::new(1.0, -1.0));
+        assert_eq!(top, Vec2::new(0.0, 1.0));
+    }
+
+    #[test]
+    fn should_spread_split_balls_evenly_around_original_direction() {
+        let directions = spread_directions(Vec2::new(0.0, 2.0), 3, 30_f32.to_radians());
+
+        assert_eq!(directions.len(), 3);
+        assert!((directions[1] - Vec2::new(0.0, 2.0)).length() < 1e-5);
+        assert!((directions[0].angle_between(directions[1]) - 30_f32.to_radians()).abs() < 1e-5);
+        assert!((directions[1].angle_between(directions[2]) - 30_f32.to_radians()).abs() < 1e-5);
+        assert!(directions
+            .iter()
+            .all(|direction| (direction.length() - 2.0).abs() < 1e-5));
+    }
+
+    #[test]
+    fn should_hold_split_of_aimed_ball_until_launch() {
+        let mut app = App::new();
+        insert_test_config(&mut app);
+        app.add_event::<SplitBallEvent>()
+            .add_state::<PlayState>()
+            .add_systems(Update, split_balls);
+        let ball = app
+            .world
+            .spawn((
+                Ball { radius: 5.0 },
+                Speed(Vec2::new(0.0, 300.0)),
+                Transform::default(),
+                Mesh2dHandle::default(),
+                Handle::<ColorMaterial>::default(),
+            ))
+            .id();
+        let ball_count = |app: &mut App| app.world.query::<&Ball>().iter(&app.world).count();
+
+        app.world
+            .resource_mut::<Events<SplitBallEvent>>()
+            .send(SplitBallEvent { ball, count: 3 });
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(ball_count(&mut app), 1);
+
+        app.insert_resource(State::new(PlayState::BallInGame));
+        app.update();
+        assert_eq!(ball_count(&mut app), 3);
+    }
+
+    #[test]
+    fn should_only_remove_ball_touching_bottom_while_others_are_in_play() {
+        let mut app = App::new();
+        app.add_event::<BallCollisionEvent>()
+            .add_event::<LastBallLost>();
+        app.add_state::<PlayState>();
+
+        app.world
+            .resource_mut::<NextState<PlayState>>()
+            .set(PlayState::BallInGame);
+
+        let bounding_box = app.world.spawn(BoundingBox).id();
+        let lost = app.world.spawn(Ball { radius: 10.0 }).id();
+        let remaining = app.world.spawn(Ball { radius: 10.0 }).id();
+
+        app.world
+            .resource_mut::<Events<BallCollisionEvent>>()
+            .send(BallCollisionEvent {
+                ball: lost,
+                with: bounding_box,
+                contact: Vec2::new(0.0, -300.0),
+                normal: Vec2::Y,
+                depth: 0.0,
+            });
+
+        app.add_systems(
+            Update,
+            (
+                apply_state_transition::<PlayState>,
+                ball_touched_bottom,
+                apply_state_transition::<PlayState>,
+            )
+                .chain(),
+        );
+
+        app.update();
+
+        let state = app.world.resource::<State<PlayState>>().get();
+        assert_eq!(*state, PlayState::BallInGame);
+        assert!(app.world.get_entity(lost).is_none());
+        assert!(app.world.get_entity(remaining).is_some());
+        assert!(app.world.resource::<Events<LastBallLost>>().is_empty());
+    }
+
+    fn ball_config(speed_progression: SpeedProgression) -> BallConfig {
+        BallConfig {
+            radius: 5.0,
+            color: Color::WHITE,
+            base_speed: 300.0,
+            max_speed: 400.0,
+            min_vertical_component: 0.3,
+            speed_progression,
+            offset_from_paddle: 40.0,
+            split_angle: 20.0,
+        }
+    }
+
+    #[test]
+    fn should_speed_ball_up_by_percent_on_every_bounce_up_to_max_speed() {
+        let config = ball_config(SpeedProgression::PerBounce { percent: 10.0 });
+        let mut ball_speed = BallSpeed::new(config.base_speed);
+
+        ball_speed.register_bounce(&config, None);
+        assert!((ball_speed.current - 330.0).abs() < 1e-3);
+
+        ball_speed.register_bounce(&config, Some(3));
+        assert!((ball_speed.current - 363.0).abs() < 1e-3);
+
+        ball_speed.register_bounce(&config, None);
+        ball_speed.register_bounce(&config, None);
+        assert_eq!(ball_speed.current, 400.0);
+    }
+
+    #[test]
+    fn should_speed_ball_up_every_n_blocks() {
+        let config = ball_config(SpeedProgression::PerBlocks {
+            blocks: NonZeroU32::new(3).unwrap(),
+            step: 25.0,
+        });
+        let mut ball_speed = BallSpeed::new(config.base_speed);
+
+        ball_speed.register_bounce(&config, Some(0));
+        ball_speed.register_bounce(&config, None);
+        ball_speed.register_bounce(&config, Some(1));
+        assert_eq!(ball_speed.current, 300.0);
+
+        ball_speed.register_bounce(&config, Some(1));
+        assert_eq!(ball_speed.current, 325.0);
+    }
+
+    #[test]
+    fn should_reject_speeding_ball_up_every_0_blocks() {
+        let progression =
+            serde_yaml::from_str::<SpeedProgression>("!PerBlocks { blocks: 0, step: 25.0 }");
+
+        assert!(progression.is_err());
+    }
+
+    #[test]
+    fn should_speed_ball_up_once_per_listed_row() {
+        let config = ball_config(SpeedProgression::Rows {
+            rows: vec![2, 0],
+            step: 40.0,
+        });
+        let mut ball_speed = BallSpeed::new(config.base_speed);
+
+        ball_speed.register_bounce(&config, Some(5));
+        ball_speed.register_bounce(&config, None);
+        assert_eq!(ball_speed.current, 300.0);
+
+        ball_speed.register_bounce(&config, Some(2));
+        ball_speed.register_bounce(&config, Some(2));
+        assert_eq!(ball_speed.current, 340.0);
+
+        ball_speed.register_bounce(&config, Some(0));
+        assert_eq!(ball_speed.current, 380.0);
+    }
+
+    #[test]
+    fn should_keep_minimal_vertical_component_of_velocity() {
+        let velocity = constrain_velocity(Vec2::new(-10.0, -0.1), 300.0, 0.3);
+
+        assert!((velocity.length() - 300.0).abs() < 1e-3);
+        assert!((velocity.y + 90.0).abs() < 1e-3);
+        assert!(velocity.x < 0.0);
+
+        let steep = constrain_velocity(Vec2::new(1.0, 2.0), 300.0, 0.3);
+        assert!((steep - Vec2::new(1.0, 2.0).normalize() * 300.0).length() < 1e-3);
+    }
+
+    #[test]
+    fn should_change_play_state_on_ball_touching_bottom() {
+        let mut app = App::new();
+        app.add_event::<BallCollisionEvent>()
+            .add_event::<LastBallLost>();
         app.add_state::<PlayState>();
 
         app.world
@@ -288,7 +1117,9 @@ mod tests {
             .send(BallCollisionEvent {
                 ball,
                 with: bounding_box,
-                collision: Collision::Bottom,
+                contact: Vec2::new(0.0, -300.0),
+                normal: Vec2::Y,
+                depth: 0.0,
             });
 
         app.add_systems(
@@ -305,5 +1136,135 @@ mod tests {
 
         let state = app.world.resource::<State<PlayState>>().get();
         assert_eq!(*state, PlayState::ReadyToShoot);
+        assert_eq!(app.world.resource::<Events<LastBallLost>>().len(), 1);
+    }
+
+    #[test]
+    fn should_keep_caught_ball_at_offset_it_landed_on() {
+        let mut app = App::new();
+        insert_test_config(&mut app);
+        app.add_systems(Update, follow_paddle);
+        app.world.spawn((
+            GlobalTransform::from_xyz(40.0, -280.0, 0.0),
+            Paddle {
+                max_deflection_angle: 1.0,
+                english: 0.0,
+            },
+        ));
+        let ball = app
+            .world
+            .spawn((
+                Ball { radius: 5.0 },
+                Transform::default(),
+                Caught::new(-25.0, 1.0),
+            ))
+            .id();
+
+        app.update();
+
+        assert_eq!(
+            app.world.get::<Transform>(ball).unwrap().translation.x,
+            15.0
+        );
+    }
+
+    fn aiming_app() -> (App, Entity) {
+        let mut app = App::new();
+        insert_test_config(&mut app);
+        app.insert_resource(BallSpeed::new(300.0))
+            .insert_resource(MousePosition {
+                world: Vec2::new(-100.0, 0.0),
+                viewport: Vec2::ZERO,
+            })
+            .add_systems(Update, calculate_ball_direction);
+        app.world.spawn((
+            GlobalTransform::from_xyz(0.0, -100.0, 0.0),
+            Paddle {
+                max_deflection_angle: 1.0,
+                english: 0.0,
+            },
+        ));
+        let ball = app
+            .world
+            .spawn((Ball { radius: 5.0 }, Speed(Vec2::new(0.0, 300.0))))
+            .id();
+        (app, ball)
+    }
+
+    #[test]
+    fn should_aim_with_right_stick() {
+        let (mut app, ball) = aiming_app();
+        let gamepad = insert_gamepad(&mut app);
+        let mut axes = app.world.resource_mut::<Axis<GamepadAxis>>();
+        axes.set(GamepadAxis::new(gamepad, GamepadAxisType::RightStickX), 1.0);
+        axes.set(GamepadAxis::new(gamepad, GamepadAxisType::RightStickY), 1.0);
+
+        app.update();
+
+        let velocity = app.world.get::<Speed>(ball).unwrap().0;
+        assert!((velocity.normalize() - Vec2::ONE.normalize()).length() < 1e-5);
+    }
+
+    #[test]
+    fn should_keep_aim_while_right_stick_rests_in_deadzone() {
+        let (mut app, ball) = aiming_app();
+        let gamepad = insert_gamepad(&mut app);
+        app.world
+            .resource_mut::<Axis<GamepadAxis>>()
+            .set(GamepadAxis::new(gamepad, GamepadAxisType::RightStickX), 0.1);
+
+        app.update();
+
+        assert_eq!(
+            app.world.get::<Speed>(ball).unwrap().0,
+            Vec2::new(0.0, 300.0)
+        );
+    }
+
+    #[test]
+    fn should_keep_stick_aim_after_stick_is_let_go() {
+        let (mut app, ball) = aiming_app();
+        let gamepad = insert_gamepad(&mut app);
+        let stick_x = GamepadAxis::new(gamepad, GamepadAxisType::RightStickX);
+        let stick_y = GamepadAxis::new(gamepad, GamepadAxisType::RightStickY);
+        let mut axes = app.world.resource_mut::<Axis<GamepadAxis>>();
+        axes.set(stick_x, 1.0);
+        axes.set(stick_y, 1.0);
+        app.update();
+
+        let mut axes = app.world.resource_mut::<Axis<GamepadAxis>>();
+        axes.set(stick_x, 0.0);
+        axes.set(stick_y, 0.0);
+        app.update();
+
+        let velocity = app.world.get::<Speed>(ball).unwrap().0;
+        assert!((velocity.normalize() - Vec2::ONE.normalize()).length() < 1e-5);
+    }
+
+    #[test]
+    fn should_aim_at_cursor_once_mouse_moves_with_untouched_gamepad_connected() {
+        let (mut app, ball) = aiming_app();
+        insert_gamepad(&mut app);
+        app.update();
+
+        *app.world.resource_mut::<MousePosition>() = MousePosition {
+            world: Vec2::new(100.0, 0.0),
+            viewport: Vec2::new(500.0, 300.0),
+        };
+        app.update();
+
+        let velocity = app.world.get::<Speed>(ball).unwrap().0;
+        assert!((velocity.normalize() - Vec2::ONE.normalize()).length() < 1e-5);
+    }
+
+    #[test]
+    fn should_aim_at_cursor_without_gamepad() {
+        let (mut app, ball) = aiming_app();
+        app.insert_resource(Axis::<GamepadAxis>::default());
+
+        app.update();
+
+        let velocity = app.world.get::<Speed>(ball).unwrap().0;
+        assert!((velocity.normalize() - Vec2::new(-1.0, 1.0).normalize()).length() < 1e-5);
     }
 }
@@ -4,9 +4,13 @@ use bevy::{prelude::*, window::PrimaryWindow};
 
 use crate::{
     ball::BallCollisionEvent,
-    config::{BlockConfig, Config, GameConfig},
-    game::{AppState, SpawningSet},
+    config::{BlockConfig, Config, GameConfig, LevelSource},
+    game::{despawn_with_component, AppState, SimulationSet, SpawningSet},
+    generator::LevelSeed,
+    layout::GridLayout,
+    level::{BlockKind, LevelBlock, Levels},
     paddle::Dimensions,
+    progressive::Progression,
 };
 
 pub struct BlockPlugin;
@@ -17,100 +21,575 @@ impl Plugin for BlockPlugin {
             OnEnter(AppState::Playing),
             spawn_blocks.in_set(SpawningSet::Blocks),
         )
-        .add_systems(Update, (hit_block,).run_if(in_state(AppState::Playing)));
+        .add_systems(OnExit(AppState::Playing), despawn_with_component::<Blocks>)
+        .add_event::<DamageBlock>()
+        .add_event::<BlockHit>()
+        .add_event::<BlockDestroyed>()
+        .configure_sets(
+            FixedUpdate,
+            (BlockSet::Hit, BlockSet::Despawn)
+                .chain()
+                .in_set(SimulationSet::Gameplay),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                hit_block.in_set(BlockSet::Hit),
+                despawn_destroyed_blocks.in_set(BlockSet::Despawn),
+            )
+                .distributive_run_if(in_state(AppState::Playing)),
+        );
     }
 }
 
+/// Order of block updates in a tick. Systems reacting to [`BlockHit`] or [`BlockDestroyed`] run
+/// between the two, while the blocks still exist.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
+pub enum BlockSet {
+    Hit,
+    Despawn,
+}
+
+/// Hits a block with something other than a ball, which hits blocks through
+/// [`BallCollisionEvent`]s. Must be sent before [`BlockSet::Hit`].
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct DamageBlock {
+    pub block: Entity,
+    pub by: DestroyedBy,
+}
+
+/// Sent whenever a block is hit, including the hit that destroys it.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct BlockHit {
+    pub block: Entity,
+    pub by: DestroyedBy,
+    pub remaining_hp: u32,
+}
+
+/// Sent once for every destroyed block, in the same tick it is despawned in. Systems between
+/// [`BlockSet::Hit`] and [`BlockSet::Despawn`] still find the block.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct BlockDestroyed {
+    pub block: Entity,
+    pub kind: BlockKind,
+    /// Where the block was, in world coordinates.
+    pub position: Vec2,
+    pub destroyed_by: DestroyedBy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DestroyedBy {
+    Ball(Entity),
+    /// Shot by a laser projectile.
+    Laser(Entity),
+    /// Caught in the explosion of another block.
+    Explosion(Entity),
+}
+
 #[derive(Component)]
 pub struct Block;
 
+/// Parent of every block of the level.
+#[derive(Component)]
+pub struct Blocks;
+
+/// Hits a block can still take before it breaks.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Health {
+    pub current: u32,
+    pub max: u32,
+}
+
+impl Health {
+    pub fn new(max: u32) -> Self {
+        Self { current: max, max }
+    }
+
+    /// Share of hit points left, between 0 and 1.
+    pub fn fraction(&self) -> f32 {
+        self.current as f32 / self.max as f32
+    }
+}
+
+/// Cell of the block grid a block was spawned in, with rows counted from the top.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GridPosition {
+    pub column: u32,
+    pub row: u32,
+}
+
 pub fn spawn_blocks(
     mut commands: Commands,
     game_config: Res<GameConfig>,
     assets: Res<Assets<Config>>,
+    levels: Levels,
     window: Query<&Window, With<PrimaryWindow>>,
-    camera: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
 ) {
     let Some(config) = assets.get(&game_config.config) else {
         panic!("game config could not be loaded")
     };
 
-    let window = window.single();
-    let (camera, camera_transform) = camera.single();
-
-    let total_block_width = total_blocks_width(window, &config.block);
+    let (blocks, seed) = levels.current_blocks(config);
+    match seed {
+        Some(seed) => commands.insert_resource(LevelSeed(seed)),
+        None => commands.remove_resource::<LevelSeed>(),
+    }
 
-    let left_upper_corner = Vec2::new(
-        total_block_width / 2.0,
-        (window.height() / 2.0 - config.block.offset_from_top)
-            - total_blocks_height(window, &config.block) / 2.0,
-    );
+    let window = window.single();
+    let playfield = Vec2::new(window.width(), window.height());
+    let layout = GridLayout::new(&config.block, playfield)
+        .unwrap_or_else(|error| panic!("blocks do not fit into the playfield: {error}"));
 
-    let Some(position) = camera.viewport_to_world_2d(camera_transform, left_upper_corner) else {
-        panic!("could not calculate left upper block in world coordinates")
-    };
+    commands.remove_resource::<Progression>();
+    if let (Some(LevelSource::Progressive(progressive)), Some(seed)) =
+        (levels.current_source(config), seed)
+    {
+        commands.insert_resource(Progression::new(*progressive, seed, layout));
+    }
 
     commands
-        .spawn((
-            Name::from("Blocks"),
-            SpatialBundle {
-                transform: Transform::from_xyz(0.0, left_upper_corner.y, 0.0),
-                ..Default::default()
-            },
-        ))
+        .spawn((Name::from("Blocks"), Blocks, SpatialBundle::default()))
         .with_children(|builder| {
-            for i in 1..config.block.columns {
-                for j in 1..config.block.rows {
-                    builder.spawn((
-                        SpriteBundle {
-                            transform: Transform::from_xyz(
-                                (position.x + config.block.width / 2.0)
-                                    + (i as f32 * config.block.width)
-                                    + (config.block.horizontal_offset * i as f32),
-                                (position.y + config.block.height)
-                                    - ((j as f32 * config.block.height)
-                                        + (config.block.vertical_offset * (j as f32 + 1.0)))
-                                    - config.block.offset_from_top,
-                                0.0,
-                            ),
-                            sprite: Sprite {
-                                color: Color::GREEN,
-                                custom_size: Some(Vec2::new(
-                                    config.block.width,
-                                    config.block.height,
-                                )),
-                                ..Default::default()
-                            },
-                            ..Default::default()
-                        },
-                        Dimensions(Vec2::new(config.block.width, config.block.height)),
-                        Block,
-                    ));
-                }
+            for block in blocks {
+                builder.spawn(block_bundle(block, layout.cell(block.column, block.row)));
             }
         });
 }
 
-fn total_blocks_width(window: &Window, block_config: &BlockConfig) -> f32 {
-    window.width()
-        - ((block_config.width * block_config.columns as f32 + 1.0)
-            + block_config.columns as f32 * block_config.horizontal_offset)
+/// Components of a block filling `cell`.
+pub fn block_bundle(block: LevelBlock, cell: Rect) -> impl Bundle {
+    (
+        SpriteBundle {
+            visibility: if block.kind == BlockKind::Invisible {
+                Visibility::Hidden
+            } else {
+                Visibility::Inherited
+            },
+            transform: Transform::from_translation(cell.center().extend(0.0)),
+            sprite: Sprite {
+                color: block.color,
+                custom_size: Some(cell.size()),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Dimensions(cell.size()),
+        Block,
+        Health::new(block.hit_points),
+        block.kind,
+        GridPosition {
+            column: block.column,
+            row: block.row,
+        },
+    )
 }
 
-fn total_blocks_height(window: &Window, block_config: &BlockConfig) -> f32 {
-    window.height()
-        - ((block_config.height * block_config.rows as f32 + 1.0)
-            + (block_config.vertical_offset * block_config.rows as f32))
+pub fn full_grid(block_config: &BlockConfig) -> Vec<LevelBlock> {
+    (0..block_config.columns)
+        .flat_map(|column| {
+            (0..block_config.rows).map(move |row| LevelBlock {
+                column,
+                row,
+                kind: BlockKind::Normal,
+                color: Color::GREEN,
+                hit_points: 1,
+            })
+        })
+        .collect()
 }
 
 fn hit_block(
-    mut commands: Commands,
-    mut reader: EventReader<BallCollisionEvent>,
-    blocks: Query<With<Block>>,
+    mut collisions: EventReader<BallCollisionEvent>,
+    mut damage: EventReader<DamageBlock>,
+    mut blocks: Query<(&mut Health, &BlockKind, Option<&mut Sprite>), With<Block>>,
+    mut visibilities: Query<&mut Visibility, With<Block>>,
+    grid: Query<(Entity, &GridPosition, &BlockKind, &GlobalTransform), With<Block>>,
+    mut hits: EventWriter<BlockHit>,
+    mut destroyed: EventWriter<BlockDestroyed>,
 ) {
+    let hits_by_balls = collisions
+        .iter()
+        .map(|event| (event.with, DestroyedBy::Ball(event.ball)));
+    let other_hits = damage.iter().map(|event| (event.block, event.by));
+
+    let mut broken = Vec::new();
+    for (block, by) in hits_by_balls.chain(other_hits) {
+        let Ok((mut health, kind, sprite)) = blocks.get_mut(block) else {
+            continue;
+        };
+        if health.current == 0 {
+            continue;
+        }
+
+        let mut revealed = false;
+        if let Ok(mut visibility) = visibilities.get_mut(block) {
+            if *kind == BlockKind::Invisible && *visibility == Visibility::Hidden {
+                *visibility = Visibility::Inherited;
+                revealed = true;
+            }
+        }
+        if kind.is_breakable() && !revealed {
+            health.current -= 1;
+        }
+
+        hits.send(BlockHit {
+            block,
+            by,
+            remaining_hp: health.current,
+        });
+        if health.current == 0 {
+            broken.push((block, by));
+        } else if let Some(mut sprite) = sprite {
+            sprite.color.set_a(damaged_alpha(&health));
+        }
+    }
+
+    if broken.is_empty() {
+        return;
+    }
+
+    let cells: Vec<_> = grid
+        .iter()
+        .map(|(entity, position, kind, _)| (entity, *position, *kind))
+        .collect();
+    for (entity, destroyed_by) in explode(broken, &cells) {
+        let Ok((_, _, kind, transform)) = grid.get(entity) else {
+            continue;
+        };
+        if let Ok((mut health, ..)) = blocks.get_mut(entity) {
+            health.current = 0;
+        }
+        destroyed.send(BlockDestroyed {
+            block: entity,
+            kind: *kind,
+            position: transform.translation().truncate(),
+            destroyed_by,
+        });
+    }
+}
+
+fn despawn_destroyed_blocks(mut commands: Commands, mut reader: EventReader<BlockDestroyed>) {
     for event in reader.iter() {
-        if blocks.get(event.with).is_ok() {
-            commands.entity(event.with).despawn_recursive();
+        commands.entity(event.block).despawn_recursive();
+    }
+}
+
+/// Returns `broken` together with every block caught in an explosion, following explosions from
+/// one explosive block to the next.
+fn explode(
+    broken: Vec<(Entity, DestroyedBy)>,
+    blocks: &[(Entity, GridPosition, BlockKind)],
+) -> Vec<(Entity, DestroyedBy)> {
+    let mut destroyed = broken;
+    let mut next = 0;
+    while let Some(&(entity, _)) = destroyed.get(next) {
+        next += 1;
+
+        let Some((_, origin, BlockKind::Explosive { radius })) =
+            blocks.iter().find(|(other, ..)| *other == entity)
+        else {
+            continue;
+        };
+
+        for (other, position, kind) in blocks {
+            let distance = origin
+                .column
+                .abs_diff(position.column)
+                .max(origin.row.abs_diff(position.row));
+            if distance <= *radius
+                && kind.is_breakable()
+                && !destroyed.iter().any(|(destroyed, _)| destroyed == other)
+            {
+                destroyed.push((*other, DestroyedBy::Explosion(entity)));
+            }
+        }
+    }
+
+    destroyed
+}
+
+/// Damaged blocks fade out, but never so much that they can't be seen anymore.
+fn damaged_alpha(health: &Health) -> f32 {
+    0.3 + 0.7 * health.fraction()
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::ManualEventReader;
+
+    use super::*;
+
+    fn hit(app: &mut App, block: Entity) {
+        app.world
+            .resource_mut::<Events<BallCollisionEvent>>()
+            .send(BallCollisionEvent {
+                ball: Entity::PLACEHOLDER,
+                with: block,
+                contact: Vec2::ZERO,
+                normal: Vec2::NEG_Y,
+                depth: 0.0,
+            });
+        app.update();
+    }
+
+    fn spawn_block(app: &mut App, kind: BlockKind, column: u32, row: u32) -> Entity {
+        app.world
+            .spawn((
+                Block,
+                Health::new(3),
+                kind,
+                GridPosition { column, row },
+                Sprite::default(),
+                Visibility::Inherited,
+                GlobalTransform::from_xyz(column as f32 * 70.0, row as f32 * -20.0, 0.0),
+            ))
+            .id()
+    }
+
+    fn block_app() -> (App, Entity) {
+        let mut app = App::new();
+        app.add_event::<BallCollisionEvent>()
+            .add_event::<DamageBlock>()
+            .add_event::<BlockHit>()
+            .add_event::<BlockDestroyed>()
+            .add_systems(Update, (hit_block, despawn_destroyed_blocks).chain());
+
+        let block = spawn_block(&mut app, BlockKind::Normal, 0, 0);
+
+        (app, block)
+    }
+
+    #[test]
+    fn should_lose_one_hit_point_per_hit_and_fade() {
+        let (mut app, block) = block_app();
+
+        hit(&mut app, block);
+        let alpha_after_one_hit = app.world.get::<Sprite>(block).unwrap().color.a();
+        hit(&mut app, block);
+
+        assert_eq!(
+            *app.world.get::<Health>(block).unwrap(),
+            Health { current: 1, max: 3 }
+        );
+        let alpha_after_two_hits = app.world.get::<Sprite>(block).unwrap().color.a();
+        assert!(alpha_after_one_hit < 1.0);
+        assert!(alpha_after_two_hits < alpha_after_one_hit);
+        assert!(alpha_after_two_hits > 0.0);
+    }
+
+    #[test]
+    fn should_only_despawn_block_on_last_hit_point() {
+        let (mut app, block) = block_app();
+
+        hit(&mut app, block);
+        hit(&mut app, block);
+        assert!(app.world.get_entity(block).is_some());
+
+        hit(&mut app, block);
+        assert!(app.world.get_entity(block).is_none());
+    }
+
+    #[test]
+    fn should_not_damage_indestructible_block() {
+        let (mut app, _) = block_app();
+        let block = spawn_block(&mut app, BlockKind::Indestructible, 1, 0);
+
+        for _ in 0..5 {
+            hit(&mut app, block);
+        }
+
+        assert_eq!(app.world.get::<Health>(block).unwrap().current, 3);
+    }
+
+    #[test]
+    fn should_only_reveal_invisible_block_on_first_hit() {
+        let (mut app, _) = block_app();
+        let block = spawn_block(&mut app, BlockKind::Invisible, 1, 0);
+        *app.world.get_mut::<Visibility>(block).unwrap() = Visibility::Hidden;
+
+        hit(&mut app, block);
+
+        assert_eq!(
+            *app.world.get::<Visibility>(block).unwrap(),
+            Visibility::Inherited
+        );
+        assert_eq!(app.world.get::<Health>(block).unwrap().current, 3);
+
+        hit(&mut app, block);
+        assert_eq!(app.world.get::<Health>(block).unwrap().current, 2);
+    }
+
+    #[test]
+    fn should_destroy_neighbours_of_exploding_block() {
+        let (mut app, far_away) = block_app();
+        let explosive = spawn_block(&mut app, BlockKind::Explosive { radius: 1 }, 5, 5);
+        let neighbour = spawn_block(&mut app, BlockKind::Normal, 6, 6);
+        let wall = spawn_block(&mut app, BlockKind::Indestructible, 4, 5);
+        app.world.get_mut::<Health>(explosive).unwrap().current = 1;
+
+        hit(&mut app, explosive);
+
+        assert!(app.world.get_entity(explosive).is_none());
+        assert!(app.world.get_entity(neighbour).is_none());
+        assert!(app.world.get_entity(wall).is_some());
+        assert!(app.world.get_entity(far_away).is_some());
+    }
+
+    #[test]
+    fn should_chain_explosions() {
+        let blocks = [0, 1, 2, 4].map(|column| {
+            (
+                Entity::from_raw(column),
+                GridPosition { column, row: 0 },
+                BlockKind::Explosive { radius: 1 },
+            )
+        });
+        let last = (
+            Entity::from_raw(3),
+            GridPosition { column: 3, row: 1 },
+            BlockKind::Normal,
+        );
+        let blocks = [blocks.as_slice(), &[last]].concat();
+
+        let ball = Entity::from_raw(10);
+        let destroyed = explode(
+            vec![(Entity::from_raw(0), DestroyedBy::Ball(ball))],
+            &blocks,
+        );
+
+        assert_eq!(
+            destroyed,
+            [
+                (0, DestroyedBy::Ball(ball)),
+                (1, DestroyedBy::Explosion(Entity::from_raw(0))),
+                (2, DestroyedBy::Explosion(Entity::from_raw(1))),
+                (3, DestroyedBy::Explosion(Entity::from_raw(2))),
+            ]
+            .map(|(index, destroyed_by)| (Entity::from_raw(index), destroyed_by)),
+            "the chain stops at the normal block, so the block in column 4 survives"
+        );
+    }
+
+    #[test]
+    fn should_report_remaining_hit_points_on_every_hit() {
+        let (mut app, block) = block_app();
+        let mut reader = ManualEventReader::<BlockHit>::default();
+
+        let remaining: Vec<_> = (0..3)
+            .map(|_| {
+                hit(&mut app, block);
+                let events = app.world.resource::<Events<BlockHit>>();
+                let hits: Vec<_> = reader.iter(events).copied().collect();
+                assert_eq!(hits.len(), 1);
+                assert_eq!(hits[0].block, block);
+                hits[0].remaining_hp
+            })
+            .collect();
+
+        assert_eq!(remaining, [2, 1, 0]);
+    }
+
+    #[derive(Resource, Default)]
+    struct SeenDestroyed(Vec<(BlockDestroyed, bool)>);
+
+    fn record_destroyed(
+        mut reader: EventReader<BlockDestroyed>,
+        blocks: Query<With<Block>>,
+        mut seen: ResMut<SeenDestroyed>,
+    ) {
+        for event in reader.iter() {
+            seen.0.push((*event, blocks.contains(event.block)));
         }
     }
+
+    #[test]
+    fn should_report_destroyed_blocks_before_despawning_them() {
+        let mut app = App::new();
+        app.add_event::<BallCollisionEvent>()
+            .add_event::<DamageBlock>()
+            .add_event::<BlockHit>()
+            .add_event::<BlockDestroyed>()
+            .init_resource::<SeenDestroyed>()
+            .add_systems(
+                Update,
+                (hit_block, record_destroyed, despawn_destroyed_blocks).chain(),
+            );
+        let explosive = spawn_block(&mut app, BlockKind::Explosive { radius: 1 }, 1, 1);
+        let neighbour = spawn_block(&mut app, BlockKind::Normal, 2, 1);
+        app.world.get_mut::<Health>(explosive).unwrap().current = 1;
+
+        hit(&mut app, explosive);
+
+        let seen = &app.world.resource::<SeenDestroyed>().0;
+        assert_eq!(
+            seen.as_slice(),
+            [
+                (
+                    BlockDestroyed {
+                        block: explosive,
+                        kind: BlockKind::Explosive { radius: 1 },
+                        position: Vec2::new(70.0, -20.0),
+                        destroyed_by: DestroyedBy::Ball(Entity::PLACEHOLDER),
+                    },
+                    true
+                ),
+                (
+                    BlockDestroyed {
+                        block: neighbour,
+                        kind: BlockKind::Normal,
+                        position: Vec2::new(140.0, -20.0),
+                        destroyed_by: DestroyedBy::Explosion(explosive),
+                    },
+                    true
+                ),
+            ]
+        );
+        assert!(app.world.get_entity(explosive).is_none());
+        assert!(app.world.get_entity(neighbour).is_none());
+    }
+
+    #[test]
+    fn should_not_take_more_than_remaining_hit_points_in_one_update() {
+        let (mut app, block) = block_app();
+        app.world.get_mut::<Health>(block).unwrap().current = 1;
+
+        for _ in 0..2 {
+            app.world
+                .resource_mut::<Events<BallCollisionEvent>>()
+                .send(BallCollisionEvent {
+                    ball: Entity::PLACEHOLDER,
+                    with: block,
+                    contact: Vec2::ZERO,
+                    normal: Vec2::NEG_Y,
+                    depth: 0.0,
+                });
+        }
+        app.update();
+
+        assert!(app.world.get_entity(block).is_none());
+    }
+
+    #[test]
+    fn should_destroy_block_damaged_by_laser() {
+        let (mut app, block) = block_app();
+        app.world.get_mut::<Health>(block).unwrap().current = 1;
+        let projectile = Entity::from_raw(42);
+
+        app.world
+            .resource_mut::<Events<DamageBlock>>()
+            .send(DamageBlock {
+                block,
+                by: DestroyedBy::Laser(projectile),
+            });
+        app.update();
+
+        let events = app.world.resource::<Events<BlockDestroyed>>();
+        let mut reader = events.get_reader();
+        let destroyed: Vec<_> = reader
+            .iter(events)
+            .map(|event| event.destroyed_by)
+            .collect();
+        assert_eq!(destroyed, [DestroyedBy::Laser(projectile)]);
+        assert!(app.world.get_entity(block).is_none());
+    }
 }
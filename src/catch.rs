@@ -0,0 +1,256 @@
+use bevy::prelude::*;
+
+use crate::{
+    ball::{paddle_bounce, Ball, BallCollisionEvent},
+    collision::Aabb,
+    config::{Config, GameConfig},
+    effect::effect_active,
+    game::{AppState, PlayState, SimulationSet},
+    paddle::{Dimensions, Paddle, Speed},
+    powerup::PowerUpKind,
+};
+
+/// Makes the ball stick to the paddle while the [`PowerUpKind::Catch`] effect is active, so the
+/// player can aim it again.
+pub struct CatchPlugin;
+
+impl Plugin for CatchPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                catch_ball.run_if(
+                    in_state(PlayState::BallInGame).and_then(effect_active(PowerUpKind::Catch)),
+                ),
+                release_caught_ball.run_if(in_state(PlayState::ReadyToShoot)),
+            )
+                .in_set(SimulationSet::Gameplay)
+                .distributive_run_if(in_state(AppState::Playing)),
+        )
+        .add_systems(OnExit(PlayState::ReadyToShoot), forget_caught_ball);
+    }
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub struct CatchConfig {
+    /// In seconds.
+    pub duration: f32,
+    /// Seconds a caught ball waits for the launch action before it is launched anyway.
+    pub release_after: f32,
+}
+
+/// Ball held by the paddle after landing on it, instead of waiting for a new life.
+#[derive(Component, Debug)]
+pub struct Caught {
+    /// Horizontal distance from the paddle's center where the ball landed.
+    pub offset: f32,
+    release: Timer,
+}
+
+impl Caught {
+    /// Caught `offset` away from the paddle's center, released after `release_after` seconds.
+    pub fn new(offset: f32, release_after: f32) -> Self {
+        Self {
+            offset,
+            release: Timer::from_seconds(release_after, TimerMode::Once),
+        }
+    }
+}
+
+/// Catches the ball landing on top of the paddle and goes back to aiming, pointing it where it
+/// would have bounced. With several balls in play they keep bouncing, since aiming stops all of
+/// them.
+fn catch_ball(
+    mut commands: Commands,
+    mut reader: EventReader<BallCollisionEvent>,
+    mut balls: Query<&mut Speed, With<Ball>>,
+    paddle: Query<(&GlobalTransform, &Dimensions, &Paddle)>,
+    mut state: ResMut<NextState<PlayState>>,
+    game_config: Res<GameConfig>,
+    assets: Res<Assets<Config>>,
+) {
+    let Some(config) = assets.get(&game_config.config) else {
+        panic!("game config could not be loaded")
+    };
+
+    if balls.iter().count() > 1 {
+        reader.clear();
+        return;
+    }
+
+    let landed = reader
+        .iter()
+        .find(|event| event.normal.y > 0.0 && paddle.contains(event.with));
+    let Some(event) = landed else {
+        return;
+    };
+    let Ok((transform, dimensions, paddle)) = paddle.get(event.with) else {
+        return;
+    };
+    let center = transform.translation().truncate();
+
+    if let Ok(mut speed) = balls.get_mut(event.ball) {
+        speed.0 = paddle_bounce(
+            speed.0,
+            event.contact.x,
+            Aabb::new(center, dimensions.0),
+            paddle.max_deflection_angle,
+            0.0,
+        );
+    }
+    commands.entity(event.ball).insert(Caught::new(
+        event.contact.x - center.x,
+        config.power_ups.catch.release_after,
+    ));
+    state.set(PlayState::ReadyToShoot);
+}
+
+fn release_caught_ball(
+    mut balls: Query<&mut Caught>,
+    mut state: ResMut<NextState<PlayState>>,
+    time: Res<FixedTime>,
+) {
+    for mut caught in &mut balls {
+        if caught.release.tick(time.period).just_finished() {
+            state.set(PlayState::BallInGame);
+        }
+    }
+}
+
+fn forget_caught_ball(mut commands: Commands, balls: Query<Entity, With<Caught>>) {
+    for ball in &balls {
+        commands.entity(ball).remove::<Caught>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::insert_test_config;
+
+    use super::*;
+
+    fn catch_app() -> (App, Entity, Entity) {
+        let mut app = App::new();
+        insert_test_config(&mut app);
+        app.add_event::<BallCollisionEvent>()
+            .add_state::<PlayState>()
+            .insert_resource(State::new(PlayState::BallInGame))
+            .insert_resource(FixedTime::new_from_secs(1.0))
+            .add_systems(
+                Update,
+                (
+                    catch_ball.run_if(in_state(PlayState::BallInGame)),
+                    release_caught_ball.run_if(in_state(PlayState::ReadyToShoot)),
+                    apply_state_transition::<PlayState>,
+                )
+                    .chain(),
+            )
+            .add_systems(OnExit(PlayState::ReadyToShoot), forget_caught_ball);
+        let paddle = app
+            .world
+            .spawn((
+                GlobalTransform::from_xyz(50.0, -280.0, 0.0),
+                Dimensions(Vec2::new(100.0, 15.0)),
+                Paddle {
+                    max_deflection_angle: 1.0,
+                    english: 0.0,
+                },
+            ))
+            .id();
+        let ball = app
+            .world
+            .spawn((Ball { radius: 5.0 }, Speed(Vec2::new(0.0, -300.0))))
+            .id();
+        (app, paddle, ball)
+    }
+
+    fn land(app: &mut App, ball: Entity, with: Entity, normal: Vec2) {
+        app.world
+            .resource_mut::<Events<BallCollisionEvent>>()
+            .send(BallCollisionEvent {
+                ball,
+                with,
+                contact: Vec2::new(30.0, -270.0),
+                normal,
+                depth: 0.0,
+            });
+        app.update();
+    }
+
+    fn state(app: &App) -> &PlayState {
+        app.world.resource::<State<PlayState>>().get()
+    }
+
+    #[test]
+    fn should_catch_ball_where_it_landed_on_paddle() {
+        let (mut app, paddle, ball) = catch_app();
+
+        land(&mut app, ball, paddle, Vec2::Y);
+
+        assert_eq!(app.world.get::<Caught>(ball).unwrap().offset, -20.0);
+        assert_eq!(*state(&app), PlayState::ReadyToShoot);
+    }
+
+    #[test]
+    fn should_point_caught_ball_up_where_it_would_have_bounced() {
+        let (mut app, paddle, ball) = catch_app();
+
+        land(&mut app, ball, paddle, Vec2::Y);
+
+        let velocity = app.world.get::<Speed>(ball).unwrap().0;
+        assert!(velocity.y > 0.0);
+        assert!(velocity.x < 0.0);
+        assert!((velocity.length() - 300.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn should_not_catch_ball_hitting_side_of_paddle() {
+        let (mut app, paddle, ball) = catch_app();
+
+        land(&mut app, ball, paddle, Vec2::X);
+
+        assert!(app.world.get::<Caught>(ball).is_none());
+        assert_eq!(*state(&app), PlayState::BallInGame);
+    }
+
+    #[test]
+    fn should_not_catch_one_of_several_balls() {
+        let (mut app, paddle, ball) = catch_app();
+        app.world.spawn((Ball { radius: 5.0 }, Speed(Vec2::Y)));
+
+        land(&mut app, ball, paddle, Vec2::Y);
+
+        assert!(app.world.get::<Caught>(ball).is_none());
+        assert_eq!(*state(&app), PlayState::BallInGame);
+    }
+
+    #[test]
+    fn should_not_catch_ball_for_landing_while_several_were_in_play() {
+        let (mut app, paddle, ball) = catch_app();
+        let other = app.world.spawn((Ball { radius: 5.0 }, Speed(Vec2::Y))).id();
+
+        land(&mut app, ball, paddle, Vec2::Y);
+        app.world.despawn(other);
+        app.update();
+
+        assert!(app.world.get::<Caught>(ball).is_none());
+        assert_eq!(*state(&app), PlayState::BallInGame);
+    }
+
+    #[test]
+    fn should_release_caught_ball_after_timeout() {
+        let (mut app, paddle, ball) = catch_app();
+        app.world
+            .resource_mut::<Assets<Config>>()
+            .iter_mut()
+            .for_each(|(_, config)| config.power_ups.catch.release_after = 2.0);
+
+        land(&mut app, ball, paddle, Vec2::Y);
+        app.update();
+        assert_eq!(*state(&app), PlayState::ReadyToShoot);
+
+        app.update();
+        assert_eq!(*state(&app), PlayState::BallInGame);
+        assert!(app.world.get::<Caught>(ball).is_none());
+    }
+}
@@ -0,0 +1,330 @@
+use bevy::prelude::*;
+
+/// Axis aligned rectangle described by its center and half of its size.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub center: Vec2,
+    pub half_extents: Vec2,
+}
+
+impl Aabb {
+    pub fn new(center: Vec2, size: Vec2) -> Self {
+        Self {
+            center,
+            half_extents: size / 2.0,
+        }
+    }
+}
+
+/// Earliest contact found along a motion segment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sweep {
+    /// Fraction of the motion travelled before the contact, in `0.0..=1.0`.
+    pub time: f32,
+    /// Normal of the touched surface, pointing back towards the ball.
+    pub normal: Vec2,
+}
+
+/// Overlap between the ball and a rectangle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contact {
+    /// Point on the surface of the rectangle closest to the ball.
+    pub point: Vec2,
+    /// Normal of the touched surface, pointing back towards the ball.
+    pub normal: Vec2,
+    /// How far the ball has to move along `normal` to stop overlapping.
+    pub depth: f32,
+}
+
+/// Returns the overlap between a circle and the outside of `aabb`, if there is one.
+pub fn contact_circle_aabb(center: Vec2, radius: f32, aabb: Aabb) -> Option<Contact> {
+    let relative = center - aabb.center;
+    let half = aabb.half_extents;
+
+    let closest = relative.clamp(-half, half);
+    let offset = relative - closest;
+    if offset == Vec2::ZERO {
+        let normal = least_penetration_normal(relative, half);
+        let penetration = (half - relative.abs()).min_element();
+        let point = relative * (Vec2::ONE - normal.abs()) + half * normal;
+        return Some(Contact {
+            point: aabb.center + point,
+            normal,
+            depth: penetration + radius,
+        });
+    }
+
+    let distance = offset.length();
+    (distance < radius).then(|| Contact {
+        point: aabb.center + closest,
+        normal: offset / distance,
+        depth: radius - distance,
+    })
+}
+
+/// Returns the deepest overlap between a circle and the inner walls of `aabb`, if there is one.
+pub fn contact_circle_inside_aabb(center: Vec2, radius: f32, aabb: Aabb) -> Option<Contact> {
+    let relative = center - aabb.center;
+    let limit = aabb.half_extents - Vec2::splat(radius);
+
+    let mut deepest: Option<Contact> = None;
+    for axis in 0..2 {
+        let depth = relative[axis].abs() - limit[axis];
+        if depth <= 0.0 || deepest.is_some_and(|contact| contact.depth >= depth) {
+            continue;
+        }
+
+        let mut normal = Vec2::ZERO;
+        normal[axis] = -relative[axis].signum();
+        let mut point = center;
+        point[axis] = aabb.center[axis] - aabb.half_extents[axis] * normal[axis];
+        deepest = Some(Contact {
+            point,
+            normal,
+            depth,
+        });
+    }
+
+    deepest
+}
+
+/// Moves a circle of `radius` from `start` by `motion` and returns the first contact with the
+/// outside of `aabb`, if any.
+///
+/// A circle that already overlaps the rectangle reports a contact at `time == 0.0`, but only while
+/// it is still moving into the rectangle.
+pub fn sweep_circle_aabb(start: Vec2, motion: Vec2, radius: f32, aabb: Aabb) -> Option<Sweep> {
+    let relative = start - aabb.center;
+    let half = aabb.half_extents;
+
+    let closest = relative.clamp(-half, half);
+    let offset = relative - closest;
+    if offset.length_squared() < radius * radius {
+        let normal = if offset == Vec2::ZERO {
+            least_penetration_normal(relative, half)
+        } else {
+            offset.normalize()
+        };
+        return (motion.dot(normal) < 0.0).then_some(Sweep { time: 0.0, normal });
+    }
+
+    let expanded = half + Vec2::splat(radius);
+    let (mut enter, mut exit) = (f32::NEG_INFINITY, f32::INFINITY);
+    let mut normal = Vec2::ZERO;
+    for axis in 0..2 {
+        if motion[axis] == 0.0 {
+            if relative[axis].abs() > expanded[axis] {
+                return None;
+            }
+            continue;
+        }
+
+        let near = (-expanded[axis].copysign(motion[axis]) - relative[axis]) / motion[axis];
+        let far = (expanded[axis].copysign(motion[axis]) - relative[axis]) / motion[axis];
+        if near > enter {
+            enter = near;
+            normal = Vec2::ZERO;
+            normal[axis] = -motion[axis].signum();
+        }
+        exit = exit.min(far);
+    }
+
+    if enter > exit || enter > 1.0 || exit <= 0.0 {
+        return None;
+    }
+
+    let time = enter.max(0.0);
+    let point = relative + motion * time;
+    if point.x.abs() > half.x && point.y.abs() > half.y {
+        let corner = Vec2::new(half.x.copysign(point.x), half.y.copysign(point.y));
+        let time = sweep_circle_point(relative, motion, radius, corner)?;
+        let normal = (relative + motion * time - corner).normalize();
+        return Some(Sweep { time, normal });
+    }
+
+    Some(Sweep { time, normal })
+}
+
+/// Moves a circle of `radius` from `start` by `motion` and returns the first contact with the
+/// inner walls of `aabb`, as if the circle was trapped inside of it.
+pub fn sweep_circle_inside_aabb(
+    start: Vec2,
+    motion: Vec2,
+    radius: f32,
+    aabb: Aabb,
+) -> Option<Sweep> {
+    let relative = start - aabb.center;
+    let limit = aabb.half_extents - Vec2::splat(radius);
+
+    let mut earliest: Option<Sweep> = None;
+    for axis in 0..2 {
+        if motion[axis] == 0.0 {
+            continue;
+        }
+
+        let wall = limit[axis].copysign(motion[axis]);
+        let distance = wall - relative[axis];
+        let time = (distance / motion[axis]).max(0.0);
+        if time > 1.0 {
+            continue;
+        }
+
+        if earliest.map_or(true, |sweep| time < sweep.time) {
+            let mut normal = Vec2::ZERO;
+            normal[axis] = -motion[axis].signum();
+            earliest = Some(Sweep { time, normal });
+        }
+    }
+
+    earliest
+}
+
+/// Time along `motion` at which a circle first touches `point`, if it happens within the motion.
+fn sweep_circle_point(start: Vec2, motion: Vec2, radius: f32, point: Vec2) -> Option<f32> {
+    let offset = start - point;
+    let a = motion.length_squared();
+    let b = 2.0 * offset.dot(motion);
+    let c = offset.length_squared() - radius * radius;
+    let discriminant = b * b - 4.0 * a * c;
+    if a == 0.0 || discriminant < 0.0 {
+        return None;
+    }
+
+    let time = (-b - discriminant.sqrt()) / (2.0 * a);
+    (0.0..=1.0).contains(&time).then_some(time)
+}
+
+fn least_penetration_normal(relative: Vec2, half: Vec2) -> Vec2 {
+    let penetration = half - relative.abs();
+    if penetration.x < penetration.y {
+        Vec2::new(relative.x.signum(), 0.0)
+    } else {
+        Vec2::new(0.0, relative.y.signum())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THIN_BLOCK: Vec2 = Vec2::new(60.0, 15.0);
+
+    #[test]
+    fn should_hit_thin_block_when_ball_moves_through_it_in_one_step() {
+        let block = Aabb::new(Vec2::new(0.0, 100.0), THIN_BLOCK);
+
+        let sweep = sweep_circle_aabb(Vec2::ZERO, Vec2::new(0.0, 1000.0), 5.0, block).unwrap();
+
+        assert_eq!(sweep.normal, Vec2::NEG_Y);
+        assert!((sweep.time - (100.0 - 7.5 - 5.0) / 1000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn should_hit_thin_block_from_the_side() {
+        let block = Aabb::new(Vec2::new(200.0, 0.0), Vec2::new(2.0, 60.0));
+
+        let sweep = sweep_circle_aabb(Vec2::ZERO, Vec2::new(5000.0, 0.0), 5.0, block).unwrap();
+
+        assert_eq!(sweep.normal, Vec2::NEG_X);
+        assert!((sweep.time - 194.0 / 5000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn should_miss_block_that_is_out_of_reach() {
+        let block = Aabb::new(Vec2::new(0.0, 100.0), THIN_BLOCK);
+
+        assert!(sweep_circle_aabb(Vec2::ZERO, Vec2::new(0.0, 50.0), 5.0, block).is_none());
+        assert!(sweep_circle_aabb(Vec2::ZERO, Vec2::new(0.0, -1000.0), 5.0, block).is_none());
+    }
+
+    #[test]
+    fn should_miss_block_when_passing_by_its_corner() {
+        let block = Aabb::new(Vec2::ZERO, Vec2::splat(10.0));
+        let start = Vec2::new(-1.0, 20.0);
+
+        assert!(sweep_circle_aabb(start, Vec2::new(30.0, -30.0), 5.0, block).is_none());
+    }
+
+    #[test]
+    fn should_hit_block_corner_with_diagonal_normal() {
+        let block = Aabb::new(Vec2::ZERO, Vec2::splat(10.0));
+        let start = Vec2::new(-20.0, -20.0);
+
+        let sweep = sweep_circle_aabb(start, Vec2::new(40.0, 40.0), 5.0, block).unwrap();
+
+        assert!((sweep.normal - Vec2::splat(-1.0).normalize()).length() < 1e-5);
+    }
+
+    #[test]
+    fn should_ignore_overlapping_block_when_moving_away() {
+        let block = Aabb::new(Vec2::ZERO, THIN_BLOCK);
+        let start = Vec2::new(0.0, 10.0);
+
+        assert!(sweep_circle_aabb(start, Vec2::new(0.0, 100.0), 5.0, block).is_none());
+        assert_eq!(
+            sweep_circle_aabb(start, Vec2::new(0.0, -100.0), 5.0, block)
+                .unwrap()
+                .time,
+            0.0
+        );
+    }
+
+    #[test]
+    fn should_hit_inner_wall_of_bounding_box() {
+        let bounding_box = Aabb::new(Vec2::ZERO, Vec2::new(800.0, 600.0));
+
+        let sweep =
+            sweep_circle_inside_aabb(Vec2::ZERO, Vec2::new(0.0, -10_000.0), 5.0, bounding_box)
+                .unwrap();
+
+        assert_eq!(sweep.normal, Vec2::Y);
+        assert!((sweep.time - 295.0 / 10_000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn should_use_real_ball_radius_for_contact() {
+        let block = Aabb::new(Vec2::ZERO, THIN_BLOCK);
+
+        let contact = contact_circle_aabb(Vec2::new(0.0, 11.5), 5.0, block).unwrap();
+
+        assert_eq!(contact.normal, Vec2::Y);
+        assert_eq!(contact.point, Vec2::new(0.0, 7.5));
+        assert!((contact.depth - 1.0).abs() < 1e-6);
+        assert!(contact_circle_aabb(Vec2::new(0.0, 12.6), 5.0, block).is_none());
+    }
+
+    #[test]
+    fn should_report_diagonal_normal_for_corner_contact() {
+        let block = Aabb::new(Vec2::ZERO, Vec2::splat(10.0));
+
+        let contact = contact_circle_aabb(Vec2::new(8.0, 8.0), 5.0, block).unwrap();
+
+        assert!((contact.normal - Vec2::ONE.normalize()).length() < 1e-6);
+        assert_eq!(contact.point, Vec2::new(5.0, 5.0));
+        assert!((contact.depth - (5.0 - 18.0_f32.sqrt())).abs() < 1e-6);
+    }
+
+    #[test]
+    fn should_push_ball_with_center_inside_block_out_through_nearest_side() {
+        let block = Aabb::new(Vec2::ZERO, THIN_BLOCK);
+
+        let contact = contact_circle_aabb(Vec2::new(28.0, 1.0), 5.0, block).unwrap();
+
+        assert_eq!(contact.normal, Vec2::X);
+        assert_eq!(contact.point, Vec2::new(30.0, 1.0));
+        assert!((contact.depth - 7.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn should_report_contact_with_inner_wall_of_bounding_box() {
+        let bounding_box = Aabb::new(Vec2::ZERO, Vec2::new(800.0, 600.0));
+
+        let contact =
+            contact_circle_inside_aabb(Vec2::new(10.0, -298.0), 5.0, bounding_box).unwrap();
+
+        assert_eq!(contact.normal, Vec2::Y);
+        assert_eq!(contact.point, Vec2::new(10.0, -300.0));
+        assert!((contact.depth - 3.0).abs() < 1e-6);
+        assert!(contact_circle_inside_aabb(Vec2::ZERO, 5.0, bounding_box).is_none());
+    }
+}
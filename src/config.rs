@@ -1,3 +1,5 @@
+use std::num::NonZeroU32;
+
 use bevy::{
     prelude::*,
     reflect::{TypePath, TypeUuid},
@@ -5,7 +7,16 @@ use bevy::{
 use bevy_asset_loader::prelude::*;
 use bevy_common_assets::yaml::YamlAssetPlugin;
 
-use crate::game::AppState;
+use crate::{
+    game::AppState,
+    generator::GeneratorConfig,
+    input::InputBindings,
+    level::{Level, LevelAssets},
+    paddle::ControlScheme,
+    powerup::PowerUpConfig,
+    progressive::ProgressiveConfig,
+    score::ScoreConfig,
+};
 
 pub struct ConfigPlugin;
 
@@ -18,11 +29,15 @@ pub struct GameConfig {
 
 impl Plugin for ConfigPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_plugins(YamlAssetPlugin::<Config>::new(&["config.yaml"]))
-            .add_loading_state(
-                LoadingState::new(AppState::AssetLoading).continue_to_state(AppState::Menu),
-            )
-            .add_collection_to_loading_state::<_, GameConfig>(AppState::AssetLoading);
+        app.add_plugins((
+            YamlAssetPlugin::<Config>::new(&["config.yaml"]),
+            YamlAssetPlugin::<Level>::new(&["level.yaml"]),
+        ))
+        .add_loading_state(
+            LoadingState::new(AppState::AssetLoading).continue_to_state(AppState::Menu),
+        )
+        .add_collection_to_loading_state::<_, GameConfig>(AppState::AssetLoading)
+        .add_collection_to_loading_state::<_, LevelAssets>(AppState::AssetLoading);
     }
 }
 
@@ -33,15 +48,56 @@ pub struct Config {
     pub paddle: PaddleConfig,
     pub block: BlockConfig,
     pub stats: StatsConfig,
+    pub simulation: SimulationConfig,
+    #[serde(default)]
+    pub gamepad: GamepadConfig,
+    /// Keys and buttons bound to each action.
+    #[serde(default)]
+    pub input: InputBindings,
+    pub power_ups: PowerUpConfig,
+    pub score: ScoreConfig,
+    /// Levels to play, in order. Without any, a single level with a block in every cell of the
+    /// grid is played.
+    #[serde(default)]
+    pub levels: Vec<LevelSource>,
 }
 
 #[derive(serde::Deserialize, Debug)]
 pub struct BallConfig {
     pub radius: f32,
     pub color: Color,
-    pub initial_speed: f32,
-    pub speed_increase: f32,
+    /// Speed the ball is launched with, in units per second.
+    pub base_speed: f32,
+    pub max_speed: f32,
+    /// Smallest share of the ball's speed that has to point up or down, between 0 and 1.
+    pub min_vertical_component: f32,
+    pub speed_progression: SpeedProgression,
     pub offset_from_paddle: f32,
+    /// Angle in degrees between the directions of balls created by a split.
+    pub split_angle: f32,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub enum SpeedProgression {
+    /// Speeds the ball up by `percent` of its current speed on every bounce.
+    PerBounce { percent: f32 },
+    /// Speeds the ball up by `step` every time it hits another `blocks` blocks.
+    PerBlocks { blocks: NonZeroU32, step: f32 },
+    /// Speeds the ball up by `step` the first time it hits a block in each of `rows`, counted
+    /// from the top.
+    Rows { rows: Vec<u32>, step: f32 },
+}
+
+/// Where the blocks of a level come from.
+#[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+pub enum LevelSource {
+    /// Path of a level file, e.g. `levels/classic.level.yaml`.
+    File(String),
+    /// A block in every cell of the grid described by [`BlockConfig`].
+    Grid,
+    Generated(GeneratorConfig),
+    /// Generated rows that keep moving down towards the paddle.
+    Progressive(ProgressiveConfig),
 }
 
 #[derive(serde::Deserialize, Debug)]
@@ -50,7 +106,21 @@ pub struct PaddleConfig {
     pub height: f32,
     pub offset_from_bottom: f32,
     pub color: Color,
-    pub initial_speed: f32,
+    /// Fastest the paddle moves, in units per second.
+    pub max_speed: f32,
+    /// How quickly the paddle speeds up while it is being moved, in units per second squared.
+    pub acceleration: f32,
+    /// How quickly the paddle slows down once it is let go, in units per second squared.
+    pub friction: f32,
+    /// In degrees, measured from the vertical.
+    pub max_deflection_angle: f32,
+    /// Share of the paddle's horizontal speed that is added to a ball bouncing off of it.
+    pub english: f32,
+    #[serde(default)]
+    pub control: ControlScheme,
+    /// Width of the walls at both sides of the playfield the paddle stops against.
+    #[serde(default)]
+    pub bumper_width: f32,
 }
 
 #[derive(serde::Deserialize, Debug)]
@@ -68,3 +138,34 @@ pub struct BlockConfig {
 pub struct StatsConfig {
     pub lifes: u32,
 }
+
+#[derive(serde::Deserialize, Debug)]
+pub struct SimulationConfig {
+    /// Simulation ticks per second.
+    pub tick_rate: f32,
+    /// Size of the cells of the grid used to find colliders near the ball.
+    pub grid_cell_size: f32,
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub struct GamepadConfig {
+    /// Share of a stick's range, between 0 and 1, that is ignored around its center.
+    pub deadzone: f32,
+}
+
+impl Default for GamepadConfig {
+    fn default() -> Self {
+        Self { deadzone: 0.15 }
+    }
+}
+
+#[cfg(test)]
+pub fn insert_test_config(app: &mut App) {
+    let config: Config = serde_yaml::from_str(include_str!("../assets/game.config.yaml"))
+        .expect("game config should be valid");
+
+    app.add_plugins(AssetPlugin::default())
+        .add_asset::<Config>();
+    let config = app.world.resource_mut::<Assets<Config>>().add(config);
+    app.insert_resource(GameConfig { config });
+}
@@ -2,8 +2,9 @@ use bevy::{prelude::*, window::PrimaryWindow};
 use bevy_prototype_debug_lines::DebugLines;
 
 use crate::{
-    ball::Ball,
+    ball::{Ball, BallCollisionEvent},
     game::{AppState, PlayState},
+    paddle::Speed,
 };
 
 pub struct DebugPlugin;
@@ -93,6 +94,7 @@ impl Plugin for DebugPlugin {
                     send_drag_event,
                     handle_drag,
                     draw_measuring_tape,
+                    draw_contact_normals,
                     update_mouse_position,
                 )
                     .distributive_run_if(in_state(AppState::Playing)),
@@ -126,14 +128,34 @@ fn draw_measuring_tape(drag: Res<Drag>, mut lines: ResMut<DebugLines>) {
     }
 }
 
+/// Shows where the ball is aimed, whether that is at the cursor or with a gamepad.
 fn draw_direction_line(
     mut lines: ResMut<DebugLines>,
-    ball: Query<&GlobalTransform, With<Ball>>,
-    mouse_position: Res<MousePosition>,
+    balls: Query<(&GlobalTransform, &Speed), With<Ball>>,
 ) {
-    let ball_transform = ball.single();
-    let end = mouse_position.world.extend(0.0);
-    
+    for (ball_transform, speed) in &balls {
+        let start = ball_transform.translation();
+        lines.line(start, start + speed.0.extend(0.0), 0.0);
+    }
+}
 
-    lines.line(ball_transform.translation(), end, 0.0);
+fn draw_contact_normals(
+    mut reader: EventReader<BallCollisionEvent>,
+    mut lines: ResMut<DebugLines>,
+) {
+    let duration = 0.5;
+    for event in reader.iter() {
+        let contact = event.contact.extend(0.0);
+        let normal = event.normal.extend(0.0);
+
+        lines.line(contact, contact + normal * 20.0, duration);
+        if event.depth > 0.0 {
+            lines.line_colored(
+                contact,
+                contact + normal * event.depth,
+                duration,
+                Color::RED,
+            );
+        }
+    }
 }
@@ -0,0 +1,478 @@
+use bevy::{ecs::system::SystemParam, prelude::*, utils::Duration};
+
+use crate::{
+    ball::{constrain_velocity, reset_speed_on_new_life, Ball, BallSpeed},
+    catch::Caught,
+    config::{Config, GameConfig},
+    game::{AppState, PlayState, SimulationSet},
+    paddle::{Dimensions, Paddle, Speed},
+    powerup::{PowerUpKind, PowerUpSet},
+};
+
+/// Applies power-ups that only last for a while and reverts them once they run out, the ball is
+/// lost or the level ends.
+pub struct EffectPlugin;
+
+impl Plugin for EffectPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ApplyEffect>()
+            .add_systems(
+                FixedUpdate,
+                (start_effects, tick_effects)
+                    .chain()
+                    .in_set(SimulationSet::Gameplay)
+                    .after(PowerUpSet)
+                    .run_if(in_state(AppState::Playing)),
+            )
+            // Cleared before the ball speed is reset, so the reset isn't undone. Catching the
+            // ball doesn't cost a life, so the effects stay.
+            .add_systems(
+                OnExit(PlayState::BallInGame),
+                clear_effects
+                    .before(reset_speed_on_new_life)
+                    .run_if(not(any_with_component::<Caught>())),
+            )
+            .add_systems(OnExit(AppState::Playing), clear_effects);
+    }
+}
+
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct TimedEffectConfig {
+    /// Factor the changed value is multiplied with.
+    pub factor: f32,
+    /// In seconds.
+    pub duration: f32,
+    pub stacking: Stacking,
+}
+
+/// What happens when an effect is applied while it is still active.
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stacking {
+    /// The duration is added to the time left.
+    Extend,
+    /// The factor is applied once more and the timer starts over.
+    Stack,
+}
+
+/// Value a timed effect changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EffectTarget {
+    PaddleWidth,
+    BallSpeed,
+}
+
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct ApplyEffect {
+    pub kind: PowerUpKind,
+    /// `None` for effects that change no value, so the factor is ignored. Systems look for the
+    /// [`ActiveEffect`] of their kind instead.
+    pub target: Option<EffectTarget>,
+    pub config: TimedEffectConfig,
+}
+
+/// Effect that is in place until its timer runs out.
+#[derive(Component, Debug)]
+pub struct ActiveEffect {
+    pub kind: PowerUpKind,
+    pub stacks: u32,
+    target: Option<EffectTarget>,
+    timer: Timer,
+    /// Product of the factors applied to the target, divided back out when the effect ends so
+    /// changes made to the target in the meantime are kept.
+    applied: f32,
+}
+
+impl ActiveEffect {
+    /// Seconds until the effect ends.
+    pub fn remaining(&self) -> f32 {
+        self.timer.remaining_secs()
+    }
+}
+
+/// Run condition that is `true` while an effect of `kind` is active.
+pub fn effect_active(kind: PowerUpKind) -> impl FnMut(Query<&ActiveEffect>) -> bool + Clone {
+    move |effects: Query<&ActiveEffect>| effects.iter().any(|effect| effect.kind == kind)
+}
+
+/// Values timed effects can change.
+#[derive(SystemParam)]
+struct EffectTargets<'w, 's> {
+    paddle: Query<'w, 's, &'static mut Dimensions, With<Paddle>>,
+    balls: Query<'w, 's, &'static mut Speed, With<Ball>>,
+    ball_speed: ResMut<'w, BallSpeed>,
+    game_config: Res<'w, GameConfig>,
+    assets: Res<'w, Assets<Config>>,
+}
+
+impl EffectTargets<'_, '_> {
+    fn get(&self, target: EffectTarget) -> Option<f32> {
+        match target {
+            EffectTarget::PaddleWidth => self.paddle.get_single().ok().map(|size| size.0.x),
+            EffectTarget::BallSpeed => Some(self.ball_speed.current),
+        }
+    }
+
+    fn set(&mut self, target: EffectTarget, value: f32) {
+        match target {
+            EffectTarget::PaddleWidth => {
+                for mut dimensions in &mut self.paddle {
+                    dimensions.0.x = value;
+                }
+            }
+            // Bounces sped the ball up to at most the max speed while it was slowed down, so
+            // dividing the factor back out could overshoot it.
+            EffectTarget::BallSpeed => {
+                let Some(config) = self.assets.get(&self.game_config.config) else {
+                    panic!("game config could not be loaded")
+                };
+
+                self.ball_speed.current = value.min(config.ball.max_speed);
+                for mut speed in &mut self.balls {
+                    speed.0 = constrain_velocity(
+                        speed.0,
+                        self.ball_speed.current,
+                        config.ball.min_vertical_component,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Multiplies the value of `target` by `factor`. Effects without a target change nothing.
+    fn scale(&mut self, target: Option<EffectTarget>, factor: f32) {
+        let Some(target) = target else {
+            return;
+        };
+        if let Some(current) = self.get(target) {
+            self.set(target, current * factor);
+        }
+    }
+
+    fn revert(&mut self, effect: &ActiveEffect) {
+        self.scale(effect.target, effect.applied.recip());
+    }
+}
+
+fn start_effects(
+    mut commands: Commands,
+    mut reader: EventReader<ApplyEffect>,
+    mut effects: Query<&mut ActiveEffect>,
+    mut targets: EffectTargets,
+) {
+    let mut started: Vec<ActiveEffect> = Vec::new();
+
+    for event in reader.iter() {
+        if event
+            .target
+            .is_some_and(|target| targets.get(target).is_none())
+        {
+            continue;
+        }
+
+        let active = effects
+            .iter_mut()
+            .map(|effect| effect.into_inner())
+            .chain(started.iter_mut())
+            .find(|effect| effect.kind == event.kind);
+        match (active, event.config.stacking) {
+            (Some(effect), Stacking::Extend) => {
+                let duration = effect.timer.duration();
+                effect
+                    .timer
+                    .set_duration(duration + Duration::from_secs_f32(event.config.duration));
+            }
+            (Some(effect), Stacking::Stack) => {
+                targets.scale(event.target, event.config.factor);
+                effect.applied *= event.config.factor;
+                effect.stacks += 1;
+                effect.timer.reset();
+            }
+            (None, _) => {
+                targets.scale(event.target, event.config.factor);
+                started.push(ActiveEffect {
+                    kind: event.kind,
+                    stacks: 1,
+                    target: event.target,
+                    timer: Timer::from_seconds(event.config.duration, TimerMode::Once),
+                    applied: event.config.factor,
+                });
+            }
+        }
+    }
+
+    for effect in started {
+        commands.spawn((Name::from(format!("{:?} effect", effect.kind)), effect));
+    }
+}
+
+fn tick_effects(
+    mut commands: Commands,
+    mut effects: Query<(Entity, &mut ActiveEffect)>,
+    mut targets: EffectTargets,
+    time: Res<FixedTime>,
+) {
+    for (entity, mut effect) in &mut effects {
+        if effect.timer.tick(time.period).finished() {
+            targets.revert(&effect);
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn clear_effects(
+    mut commands: Commands,
+    effects: Query<(Entity, &ActiveEffect)>,
+    mut targets: EffectTargets,
+) {
+    for (entity, effect) in &effects {
+        targets.revert(effect);
+        commands.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::insert_test_config;
+
+    use super::*;
+
+    const WIDE: TimedEffectConfig = TimedEffectConfig {
+        factor: 1.5,
+        duration: 2.0,
+        stacking: Stacking::Extend,
+    };
+
+    const SLOW: TimedEffectConfig = TimedEffectConfig {
+        factor: 0.5,
+        duration: 2.0,
+        stacking: Stacking::Stack,
+    };
+
+    fn effect_app() -> (App, Entity, Entity) {
+        let mut app = App::new();
+        insert_test_config(&mut app);
+        app.add_event::<ApplyEffect>()
+            .insert_resource(FixedTime::new_from_secs(1.0))
+            .insert_resource(BallSpeed::new(400.0))
+            .add_systems(Update, (start_effects, tick_effects).chain());
+        let paddle = app
+            .world
+            .spawn((
+                Dimensions(Vec2::new(100.0, 15.0)),
+                Paddle {
+                    max_deflection_angle: 1.0,
+                    english: 0.0,
+                },
+            ))
+            .id();
+        let ball = app
+            .world
+            .spawn((Ball { radius: 5.0 }, Speed(Vec2::new(0.0, 400.0))))
+            .id();
+        (app, paddle, ball)
+    }
+
+    fn apply(app: &mut App, kind: PowerUpKind, target: EffectTarget, config: TimedEffectConfig) {
+        app.world
+            .resource_mut::<Events<ApplyEffect>>()
+            .send(ApplyEffect {
+                kind,
+                target: Some(target),
+                config,
+            });
+    }
+
+    fn paddle_width(app: &App, paddle: Entity) -> f32 {
+        app.world.get::<Dimensions>(paddle).unwrap().0.x
+    }
+
+    fn effects(app: &mut App) -> Vec<(u32, f32)> {
+        app.world
+            .query::<&ActiveEffect>()
+            .iter(&app.world)
+            .map(|effect| (effect.stacks, effect.remaining()))
+            .collect()
+    }
+
+    #[test]
+    fn should_revert_effect_when_it_runs_out() {
+        let (mut app, paddle, _) = effect_app();
+
+        apply(
+            &mut app,
+            PowerUpKind::WidePaddle,
+            EffectTarget::PaddleWidth,
+            WIDE,
+        );
+        app.update();
+        assert_eq!(paddle_width(&app, paddle), 150.0);
+        assert_eq!(effects(&mut app), vec![(1, 2.0)]);
+
+        app.update();
+        assert_eq!(effects(&mut app), vec![(1, 1.0)]);
+
+        app.update();
+        assert_eq!(paddle_width(&app, paddle), 100.0);
+        assert!(effects(&mut app).is_empty());
+    }
+
+    #[test]
+    fn should_extend_effect_applied_again() {
+        let (mut app, paddle, _) = effect_app();
+
+        apply(
+            &mut app,
+            PowerUpKind::WidePaddle,
+            EffectTarget::PaddleWidth,
+            WIDE,
+        );
+        app.update();
+        apply(
+            &mut app,
+            PowerUpKind::WidePaddle,
+            EffectTarget::PaddleWidth,
+            WIDE,
+        );
+        app.update();
+
+        assert_eq!(paddle_width(&app, paddle), 150.0);
+        assert_eq!(effects(&mut app), vec![(1, 3.0)]);
+    }
+
+    #[test]
+    fn should_stack_effect_applied_again() {
+        let (mut app, _, ball) = effect_app();
+
+        apply(
+            &mut app,
+            PowerUpKind::SlowBall,
+            EffectTarget::BallSpeed,
+            SLOW,
+        );
+        app.update();
+        apply(
+            &mut app,
+            PowerUpKind::SlowBall,
+            EffectTarget::BallSpeed,
+            SLOW,
+        );
+        app.update();
+
+        assert_eq!(app.world.resource::<BallSpeed>().current, 100.0);
+        assert_eq!(
+            app.world.get::<Speed>(ball).unwrap().0,
+            Vec2::new(0.0, 100.0)
+        );
+        assert_eq!(effects(&mut app), vec![(2, 1.0)]);
+
+        app.update();
+        assert_eq!(app.world.resource::<BallSpeed>().current, 400.0);
+        assert_eq!(
+            app.world.get::<Speed>(ball).unwrap().0,
+            Vec2::new(0.0, 400.0)
+        );
+    }
+
+    #[test]
+    fn should_keep_speed_gained_while_ball_was_slowed_down() {
+        let (mut app, _, _) = effect_app();
+
+        apply(
+            &mut app,
+            PowerUpKind::SlowBall,
+            EffectTarget::BallSpeed,
+            SLOW,
+        );
+        app.update();
+        assert_eq!(app.world.resource::<BallSpeed>().current, 200.0);
+        app.world.resource_mut::<BallSpeed>().current = 250.0;
+        app.update();
+        app.update();
+
+        assert!(effects(&mut app).is_empty());
+        assert_eq!(app.world.resource::<BallSpeed>().current, 500.0);
+    }
+
+    #[test]
+    fn should_not_exceed_max_speed_reached_while_ball_was_slowed_down() {
+        let (mut app, _, ball) = effect_app();
+        app.world
+            .resource_mut::<Assets<Config>>()
+            .iter_mut()
+            .for_each(|(_, config)| {
+                config.ball.max_speed = 600.0;
+                config.ball.min_vertical_component = 0.5;
+            });
+        app.world.get_mut::<Speed>(ball).unwrap().0 = Vec2::new(400.0, 0.0);
+
+        apply(
+            &mut app,
+            PowerUpKind::SlowBall,
+            EffectTarget::BallSpeed,
+            SLOW,
+        );
+        app.update();
+        app.world.resource_mut::<BallSpeed>().current = 600.0;
+        app.update();
+        app.update();
+
+        assert!(effects(&mut app).is_empty());
+        assert_eq!(app.world.resource::<BallSpeed>().current, 600.0);
+        let velocity = app.world.get::<Speed>(ball).unwrap().0;
+        assert!((velocity.length() - 600.0).abs() < 0.001);
+        assert!(velocity.y.abs() >= 0.5 * 600.0 - 0.001);
+    }
+
+    #[test]
+    fn should_track_effect_without_target_and_change_nothing() {
+        let (mut app, paddle, ball) = effect_app();
+
+        app.world
+            .resource_mut::<Events<ApplyEffect>>()
+            .send(ApplyEffect {
+                kind: PowerUpKind::Laser,
+                target: None,
+                config: WIDE,
+            });
+        app.update();
+        assert_eq!(effects(&mut app), vec![(1, 2.0)]);
+
+        app.update();
+        app.update();
+        assert!(effects(&mut app).is_empty());
+        assert_eq!(paddle_width(&app, paddle), 100.0);
+        assert_eq!(app.world.resource::<BallSpeed>().current, 400.0);
+        assert_eq!(
+            app.world.get::<Speed>(ball).unwrap().0,
+            Vec2::new(0.0, 400.0)
+        );
+    }
+
+    #[test]
+    fn should_clear_effects_when_ball_is_lost() {
+        let (mut app, paddle, _) = effect_app();
+        app.add_state::<PlayState>()
+            .insert_resource(State::new(PlayState::BallInGame))
+            .add_systems(OnExit(PlayState::BallInGame), clear_effects)
+            .add_systems(Update, apply_state_transition::<PlayState>);
+
+        apply(
+            &mut app,
+            PowerUpKind::WidePaddle,
+            EffectTarget::PaddleWidth,
+            TimedEffectConfig {
+                duration: 10.0,
+                ..WIDE
+            },
+        );
+        app.update();
+        assert_eq!(paddle_width(&app, paddle), 150.0);
+        app.world
+            .resource_mut::<NextState<PlayState>>()
+            .set(PlayState::ReadyToShoot);
+        app.update();
+
+        assert_eq!(paddle_width(&app, paddle), 100.0);
+        assert!(effects(&mut app).is_empty());
+    }
+}
@@ -1,13 +1,27 @@
-use bevy::{input::common_conditions::input_toggle_active, prelude::*, window::PrimaryWindow};
+use bevy::{
+    prelude::*,
+    transform::systems::{propagate_transforms, sync_simple_transforms},
+    window::PrimaryWindow,
+};
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 use bevy_prototype_debug_lines::*;
 
 use crate::{
     ball::BallPlugin,
     block::BlockPlugin,
-    config::ConfigPlugin,
+    catch::CatchPlugin,
+    config::{Config, ConfigPlugin, GameConfig},
     debug::DebugPlugin,
+    effect::EffectPlugin,
+    gamepad::GamepadPlugin,
+    input::{action_toggle_active, Action, ActionPlugin},
+    laser::LaserPlugin,
+    level::LevelPlugin,
     paddle::{Dimensions, PaddlePlugin},
+    powerup::PowerUpPlugin,
+    progressive::ProgressivePlugin,
+    score::ScorePlugin,
+    spatial::{forget_removed_colliders, update_spatial_grid, SpatialGrid},
     stats::StatsPlugin,
     ui::UiPlugin,
 };
@@ -20,6 +34,8 @@ pub enum AppState {
     AssetLoading,
     Menu,
     Playing,
+    LevelComplete,
+    Victory,
     GameOver,
 }
 
@@ -38,16 +54,59 @@ pub enum SpawningSet {
     Blocks,
 }
 
+/// Parts of a single simulation tick, in the order they run in [`FixedUpdate`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
+pub enum SimulationSet {
+    Input,
+    Transforms,
+    Physics,
+    Gameplay,
+}
+
 #[derive(Component)]
 pub struct BoundingBox;
 
+/// Runs the gameplay simulation in [`FixedUpdate`] at the tick rate from the config, so the same
+/// inputs always play out the same way regardless of frame rate.
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(
+            FixedUpdate,
+            (
+                SimulationSet::Input,
+                SimulationSet::Transforms,
+                SimulationSet::Physics,
+                SimulationSet::Gameplay,
+            )
+                .chain(),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                (sync_simple_transforms, propagate_transforms),
+                update_spatial_grid.run_if(resource_exists::<SpatialGrid>()),
+            )
+                .chain()
+                .in_set(SimulationSet::Transforms),
+        )
+        .add_systems(
+            Last,
+            forget_removed_colliders.run_if(resource_exists::<SpatialGrid>()),
+        )
+        .add_systems(OnEnter(AppState::Menu), configure_simulation);
+    }
+}
+
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app.add_state::<AppState>()
             .add_state::<PlayState>()
             .add_plugins((
                 DefaultPlugins,
-                WorldInspectorPlugin::default().run_if(input_toggle_active(true, KeyCode::Slash)),
+                WorldInspectorPlugin::default()
+                    .run_if(action_toggle_active(true, Action::ToggleInspector)),
                 DebugLinesPlugin::default(),
                 BallPlugin,
                 ConfigPlugin,
@@ -55,7 +114,12 @@ impl Plugin for GamePlugin {
                 UiPlugin,
                 BlockPlugin,
                 DebugPlugin,
-                StatsPlugin,
+                (StatsPlugin, ScorePlugin),
+                LevelPlugin,
+                ProgressivePlugin,
+                (PowerUpPlugin, EffectPlugin, LaserPlugin, CatchPlugin),
+                (GamepadPlugin, ActionPlugin),
+                SimulationPlugin,
             ))
             .configure_sets(
                 OnEnter(AppState::Playing),
@@ -68,6 +132,8 @@ impl Plugin for GamePlugin {
                     .chain(),
             )
             .add_systems(Update, start_game.run_if(in_state(AppState::Menu)))
+            .add_systems(Update, toggle_pause.run_if(in_state(AppState::Playing)))
+            .add_systems(OnExit(AppState::Playing), resume)
             .add_systems(
                 OnEnter(AppState::Playing),
                 apply_deferred.in_set(SpawningSet::Deferred),
@@ -98,14 +164,179 @@ fn spawn_bounding_box(mut commands: Commands, window: Query<&Window, With<Primar
     ));
 }
 
-fn start_game(input: Res<Input<KeyCode>>, mut state: ResMut<NextState<AppState>>) {
-    if input.just_pressed(KeyCode::Return) {
+fn configure_simulation(
+    mut commands: Commands,
+    game_config: Res<GameConfig>,
+    assets: Res<Assets<Config>>,
+) {
+    let Some(config) = assets.get(&game_config.config) else {
+        panic!("game config could not be loaded")
+    };
+
+    commands.insert_resource(FixedTime::new_from_secs(1.0 / config.simulation.tick_rate));
+    commands.insert_resource(SpatialGrid::new(config.simulation.grid_cell_size));
+}
+
+fn start_game(actions: Res<Input<Action>>, mut state: ResMut<NextState<AppState>>) {
+    if actions.just_pressed(Action::Confirm) {
         state.set(AppState::Playing)
     }
 }
 
+/// Pausing stops the clock, so nothing in [`FixedUpdate`] runs until the game is resumed. Gameplay
+/// systems in [`Update`] check [`not_paused`] instead.
+fn toggle_pause(actions: Res<Input<Action>>, mut time: ResMut<Time>) {
+    if !actions.just_pressed(Action::Pause) {
+        return;
+    }
+
+    if time.is_paused() {
+        time.unpause();
+    } else {
+        time.pause();
+    }
+}
+
+/// Run condition for gameplay systems outside of [`FixedUpdate`], so input doesn't change the game
+/// while it is paused.
+pub fn not_paused(time: Res<Time>) -> bool {
+    !time.is_paused()
+}
+
+fn resume(mut time: ResMut<Time>) {
+    time.unpause();
+}
+
 pub fn despawn_with_component<C: Component>(mut commands: Commands, query: Query<Entity, With<C>>) {
     for entity in &query {
         commands.entity(entity).despawn_recursive()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ball::{Ball, BallSpeed, Bounces},
+        block::{Block, GridPosition, Health},
+        config::insert_test_config,
+        debug::MousePosition,
+        level::BlockKind,
+        paddle::{ControlScheme, Paddle, PaddleMotion, Speed},
+        stats::Lives,
+    };
+
+    use super::*;
+
+    fn simulation_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            SimulationPlugin,
+            BallPlugin,
+            PaddlePlugin,
+            BlockPlugin,
+            StatsPlugin,
+        ));
+        insert_test_config(&mut app);
+
+        app.add_state::<AppState>()
+            .add_state::<PlayState>()
+            .insert_resource(State::new(AppState::Playing))
+            .insert_resource(State::new(PlayState::BallInGame))
+            .insert_resource(FixedTime::new_from_secs(1.0 / 60.0))
+            .insert_resource(Lives(3))
+            .insert_resource(BallSpeed::new(300.0))
+            .insert_resource(SpatialGrid::new(64.0))
+            .insert_resource(Input::<Action>::default())
+            .insert_resource(Axis::<GamepadAxis>::default())
+            .insert_resource(MousePosition::default())
+            .insert_resource(ControlScheme::Keyboard);
+
+        app.world.spawn((
+            TransformBundle::default(),
+            Dimensions(Vec2::new(800.0, 600.0)),
+            BoundingBox,
+        ));
+        app.world.spawn((
+            TransformBundle::from_transform(Transform::from_xyz(0.0, -280.0, 0.0)),
+            Dimensions(Vec2::new(150.0, 15.0)),
+            Paddle {
+                max_deflection_angle: 60_f32.to_radians(),
+                english: 0.0,
+            },
+            Speed(Vec2::ZERO),
+            PaddleMotion {
+                max_speed: 450.0,
+                acceleration: 3000.0,
+                friction: 2500.0,
+            },
+        ));
+        app.world.spawn((
+            TransformBundle::from_transform(Transform::from_xyz(0.0, -240.0, 0.0)),
+            Ball { radius: 5.0 },
+            Speed(Vec2::new(180.0, 240.0)),
+        ));
+        app.world
+            .spawn(TransformBundle::from_transform(Transform::from_xyz(
+                0.0, 150.0, 0.0,
+            )))
+            .with_children(|builder| {
+                for column in -5..5 {
+                    for row in 0..4 {
+                        builder.spawn((
+                            TransformBundle::from_transform(Transform::from_xyz(
+                                column as f32 * 70.0 + 35.0,
+                                row as f32 * 20.0,
+                                0.0,
+                            )),
+                            Dimensions(Vec2::new(60.0, 15.0)),
+                            Block,
+                            Health::new(1),
+                            BlockKind::Normal,
+                            GridPosition {
+                                column: (column + 5) as u32,
+                                row: 3 - row,
+                            },
+                        ));
+                    }
+                }
+            });
+
+        app
+    }
+
+    fn run_simulation(app: &mut App) -> Vec<Transform> {
+        for tick in 0..1200 {
+            let mut actions = app.world.resource_mut::<Input<Action>>();
+            match tick {
+                0 => actions.press(Action::MoveLeft),
+                90 => {
+                    actions.release(Action::MoveLeft);
+                    actions.press(Action::MoveRight);
+                }
+                400 => actions.release(Action::MoveRight),
+                _ => {}
+            }
+
+            app.world.run_schedule(FixedUpdate);
+        }
+
+        app.world
+            .query_filtered::<&Transform, Or<(With<Ball>, With<Paddle>, With<Block>)>>()
+            .iter(&app.world)
+            .copied()
+            .collect()
+    }
+
+    #[test]
+    fn should_simulate_identical_inputs_identically() {
+        let mut first = simulation_app();
+        let mut second = simulation_app();
+
+        let first_transforms = run_simulation(&mut first);
+        let second_transforms = run_simulation(&mut second);
+
+        assert_eq!(first_transforms, second_transforms);
+        assert!(first.world.resource::<Bounces>().0 > 0);
+    }
+}
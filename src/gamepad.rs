@@ -0,0 +1,167 @@
+use bevy::{
+    ecs::system::SystemParam,
+    input::{
+        gamepad::{GamepadConnection, GamepadConnectionEvent},
+        InputSystem,
+    },
+    prelude::*,
+};
+
+use crate::config::{Config, GameConfig};
+
+/// Gamepad the player is using, the first one that got connected.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActiveGamepad(pub Gamepad);
+
+pub struct GamepadPlugin;
+
+impl Plugin for GamepadPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PreUpdate,
+            track_active_gamepad
+                .after(InputSystem)
+                .run_if(on_event::<GamepadConnectionEvent>()),
+        );
+    }
+}
+
+fn track_active_gamepad(
+    mut commands: Commands,
+    mut reader: EventReader<GamepadConnectionEvent>,
+    active: Option<Res<ActiveGamepad>>,
+) {
+    let mut gamepad = active.map(|active| active.0);
+    for event in reader.iter() {
+        match (&event.connection, gamepad) {
+            (GamepadConnection::Connected(_), None) => gamepad = Some(event.gamepad),
+            (GamepadConnection::Disconnected, Some(active)) if active == event.gamepad => {
+                gamepad = None
+            }
+            _ => {}
+        }
+    }
+
+    match gamepad {
+        Some(gamepad) => commands.insert_resource(ActiveGamepad(gamepad)),
+        None => commands.remove_resource::<ActiveGamepad>(),
+    }
+}
+
+/// Reads the sticks of the [`ActiveGamepad`], if there is one. Its buttons are bound to
+/// [`Action`](crate::input::Action)s.
+#[derive(SystemParam)]
+pub struct GamepadInput<'w> {
+    active: Option<Res<'w, ActiveGamepad>>,
+    axes: Res<'w, Axis<GamepadAxis>>,
+    game_config: Res<'w, GameConfig>,
+    assets: Res<'w, Assets<Config>>,
+}
+
+impl GamepadInput<'_> {
+    pub fn is_active(&self) -> bool {
+        self.active.is_some()
+    }
+
+    /// How far the paddle should move this tick, between -1 for full speed to the left and 1 for
+    /// full speed to the right. The d-pad is bound to actions instead.
+    pub fn paddle_direction(&self) -> f32 {
+        let Some(ActiveGamepad(gamepad)) = self.active.as_deref().copied() else {
+            return 0.0;
+        };
+
+        self.stick(
+            gamepad,
+            GamepadAxisType::LeftStickX,
+            GamepadAxisType::LeftStickY,
+        )
+        .x
+    }
+
+    /// Direction the right stick points in, unless it rests inside the deadzone.
+    pub fn aim(&self) -> Option<Vec2> {
+        let ActiveGamepad(gamepad) = *self.active.as_deref()?;
+        let aim = self.stick(
+            gamepad,
+            GamepadAxisType::RightStickX,
+            GamepadAxisType::RightStickY,
+        );
+        (aim != Vec2::ZERO).then_some(aim)
+    }
+
+    fn stick(&self, gamepad: Gamepad, x: GamepadAxisType, y: GamepadAxisType) -> Vec2 {
+        let Some(config) = self.assets.get(&self.game_config.config) else {
+            panic!("game config could not be loaded")
+        };
+
+        let axis = |axis_type| {
+            self.axes
+                .get(GamepadAxis::new(gamepad, axis_type))
+                .unwrap_or(0.0)
+        };
+        Vec2::new(
+            apply_deadzone(axis(x), config.gamepad.deadzone),
+            apply_deadzone(axis(y), config.gamepad.deadzone),
+        )
+    }
+}
+
+/// Ignores deflections up to `deadzone` and scales the rest back to the full `-1.0..=1.0` range,
+/// so the paddle can still crawl just outside of the deadzone.
+pub fn apply_deadzone(value: f32, deadzone: f32) -> f32 {
+    if value.abs() <= deadzone {
+        return 0.0;
+    }
+    (value.signum() * (value.abs() - deadzone) / (1.0 - deadzone)).clamp(-1.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::input::gamepad::GamepadInfo;
+
+    use super::*;
+
+    #[test]
+    fn should_ignore_deflection_inside_deadzone() {
+        assert_eq!(apply_deadzone(0.1, 0.2), 0.0);
+        assert_eq!(apply_deadzone(-0.2, 0.2), 0.0);
+    }
+
+    #[test]
+    fn should_scale_deflection_outside_deadzone() {
+        assert_eq!(apply_deadzone(0.75, 0.5), 0.5);
+        assert_eq!(apply_deadzone(-1.0, 0.2), -1.0);
+        assert_eq!(apply_deadzone(1.5, 0.2), 1.0);
+    }
+
+    #[test]
+    fn should_keep_first_connected_gamepad_until_it_disconnects() {
+        let mut app = App::new();
+        app.add_event::<GamepadConnectionEvent>()
+            .add_systems(Update, track_active_gamepad);
+        let connect = |app: &mut App, id, connection| {
+            app.world
+                .resource_mut::<Events<GamepadConnectionEvent>>()
+                .send(GamepadConnectionEvent::new(Gamepad::new(id), connection));
+            app.update();
+        };
+        let connected = || {
+            GamepadConnection::Connected(GamepadInfo {
+                name: "Pad".to_string(),
+            })
+        };
+
+        connect(&mut app, 0, connected());
+        connect(&mut app, 1, connected());
+        assert_eq!(
+            app.world.get_resource::<ActiveGamepad>(),
+            Some(&ActiveGamepad(Gamepad::new(0)))
+        );
+
+        connect(&mut app, 1, GamepadConnection::Disconnected);
+        assert!(app.world.contains_resource::<ActiveGamepad>());
+
+        connect(&mut app, 0, GamepadConnection::Disconnected);
+        assert!(!app.world.contains_resource::<ActiveGamepad>());
+    }
+}
@@ -0,0 +1,340 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+
+use crate::level::{BlockKind, LevelBlock};
+
+/// Seed of the generated level being played, shown so players can share a layout.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelSeed(pub u64);
+
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct GeneratorConfig {
+    pub pattern: Pattern,
+    /// Share of the cells the pattern may fill, between 0 and 1.
+    pub density: f32,
+    /// Without one, every game gets a new layout.
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pattern {
+    /// Random blocks mirrored around the middle column.
+    Symmetric,
+    /// Rows that get wider towards the bottom.
+    Pyramid,
+    /// Random blocks all over the grid.
+    Noise,
+    /// Walls of a maze, with the blocks at the crossings unbreakable.
+    Maze,
+}
+
+/// Small, fast generator whose output only depends on its seed.
+///
+/// See <https://prng.di.unimi.it/splitmix64.c>.
+#[derive(Debug, Clone)]
+pub struct SplitMix64(u64);
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniformly distributed in `0.0..1.0`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Uniformly distributed in `0..bound`.
+    pub fn below(&mut self, bound: u32) -> u32 {
+        (self.next_u64() % bound as u64) as u32
+    }
+}
+
+/// Seed for levels that don't have one configured.
+pub fn random_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_nanos() as u64)
+}
+
+/// Builds the blocks of a `columns` by `rows` grid following `pattern`. The same arguments
+/// always produce the same blocks, and there is always at least one block to break.
+pub fn generate(
+    pattern: Pattern,
+    density: f32,
+    seed: u64,
+    columns: u32,
+    rows: u32,
+) -> Vec<LevelBlock> {
+    let mut rng = SplitMix64::new(seed);
+    let mut cells = match pattern {
+        Pattern::Symmetric => symmetric(&mut rng, density, columns, rows),
+        Pattern::Pyramid => pyramid(&mut rng, density, columns, rows),
+        Pattern::Noise => noise(&mut rng, density, columns, rows),
+        Pattern::Maze => maze(&mut rng, density, columns, rows),
+    };
+    ensure_breakable(&mut cells, &mut rng, columns, rows);
+    cells.sort_by_key(|(column, row, _)| (*row, *column));
+
+    cells
+        .into_iter()
+        .map(|(column, row, kind)| LevelBlock {
+            column,
+            row,
+            kind,
+            color: row_color(row, rows, kind),
+            hit_points: 1,
+        })
+        .collect()
+}
+
+type Cell = (u32, u32, BlockKind);
+
+fn noise(rng: &mut SplitMix64, density: f32, columns: u32, rows: u32) -> Vec<Cell> {
+    let mut cells = Vec::new();
+    for row in 0..rows {
+        for column in 0..columns {
+            if rng.next_f32() < density {
+                cells.push((column, row, BlockKind::Normal));
+            }
+        }
+    }
+    cells
+}
+
+fn symmetric(rng: &mut SplitMix64, density: f32, columns: u32, rows: u32) -> Vec<Cell> {
+    let mut cells = Vec::new();
+    for row in 0..rows {
+        for column in 0..columns.div_ceil(2) {
+            if rng.next_f32() < density {
+                let mirrored = columns - 1 - column;
+                cells.push((column, row, BlockKind::Normal));
+                if mirrored != column {
+                    cells.push((mirrored, row, BlockKind::Normal));
+                }
+            }
+        }
+    }
+    cells
+}
+
+fn pyramid(rng: &mut SplitMix64, density: f32, columns: u32, rows: u32) -> Vec<Cell> {
+    let mut cells = Vec::new();
+    for row in 0..rows {
+        let width = (columns * (row + 1)).div_ceil(rows);
+        let first = (columns - width) / 2;
+        for column in first..first + width {
+            if rng.next_f32() < density {
+                cells.push((column, row, BlockKind::Normal));
+            }
+        }
+    }
+    cells
+}
+
+/// Carves a maze with a randomized depth-first search through the cells with even coordinates
+/// and keeps `density` of the remaining walls.
+fn maze(rng: &mut SplitMix64, density: f32, columns: u32, rows: u32) -> Vec<Cell> {
+    let index = |column: u32, row: u32| (row * columns + column) as usize;
+    let mut open = vec![false; (columns * rows) as usize];
+
+    let mut stack: Vec<(u32, u32)> = vec![(0, 0)];
+    open[0] = true;
+    while let Some(&(column, row)) = stack.last() {
+        let neighbours: Vec<(u32, u32)> = [(0, -2), (2, 0), (0, 2), (-2, 0)]
+            .into_iter()
+            .filter_map(|(dx, dy)| {
+                let column = column.checked_add_signed(dx)?;
+                let row = row.checked_add_signed(dy)?;
+                (column < columns && row < rows && !open[index(column, row)])
+                    .then_some((column, row))
+            })
+            .collect();
+
+        if neighbours.is_empty() {
+            stack.pop();
+            continue;
+        }
+
+        let (next_column, next_row) = neighbours[rng.below(neighbours.len() as u32) as usize];
+        open[index((column + next_column) / 2, (row + next_row) / 2)] = true;
+        open[index(next_column, next_row)] = true;
+        stack.push((next_column, next_row));
+    }
+
+    let mut cells = Vec::new();
+    for row in 0..rows {
+        for column in 0..columns {
+            if open[index(column, row)] {
+                continue;
+            }
+
+            if column % 2 == 1 && row % 2 == 1 {
+                cells.push((column, row, BlockKind::Indestructible));
+            } else if rng.next_f32() < density {
+                cells.push((column, row, BlockKind::Normal));
+            }
+        }
+    }
+    cells
+}
+
+/// Puts a block into a random empty cell when the pattern left nothing to break, as the level
+/// would be complete right away otherwise. Without an empty cell, an unbreakable block is turned
+/// into a breakable one instead.
+fn ensure_breakable(cells: &mut Vec<Cell>, rng: &mut SplitMix64, columns: u32, rows: u32) {
+    if cells.iter().any(|(_, _, kind)| kind.is_breakable()) {
+        return;
+    }
+
+    let empty: Vec<(u32, u32)> = (0..rows)
+        .flat_map(|row| (0..columns).map(move |column| (column, row)))
+        .filter(|&(column, row)| !cells.iter().any(|cell| (cell.0, cell.1) == (column, row)))
+        .collect();
+
+    if !empty.is_empty() {
+        let (column, row) = empty[rng.below(empty.len() as u32) as usize];
+        cells.push((column, row, BlockKind::Normal));
+    } else if !cells.is_empty() {
+        let index = rng.below(cells.len() as u32) as usize;
+        cells[index].2 = BlockKind::Normal;
+    }
+}
+
+fn row_color(row: u32, rows: u32, kind: BlockKind) -> Color {
+    if kind == BlockKind::Indestructible {
+        return Color::GRAY;
+    }
+    Color::hsl(360.0 * row as f32 / rows as f32, 0.75, 0.55)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATTERNS: [Pattern; 4] = [
+        Pattern::Symmetric,
+        Pattern::Pyramid,
+        Pattern::Noise,
+        Pattern::Maze,
+    ];
+
+    fn cells(blocks: &[LevelBlock]) -> Vec<(u32, u32, BlockKind)> {
+        blocks
+            .iter()
+            .map(|block| (block.column, block.row, block.kind))
+            .collect()
+    }
+
+    #[test]
+    fn should_generate_same_layout_for_same_seed() {
+        for pattern in PATTERNS {
+            let first = generate(pattern, 0.6, 42, 15, 10);
+            let second = generate(pattern, 0.6, 42, 15, 10);
+
+            assert_eq!(first, second, "{pattern:?}");
+            assert!(!first.is_empty(), "{pattern:?}");
+        }
+    }
+
+    #[test]
+    fn should_generate_different_layouts_for_different_seeds() {
+        for pattern in [Pattern::Symmetric, Pattern::Noise, Pattern::Maze] {
+            let first = generate(pattern, 0.6, 1, 15, 10);
+            let second = generate(pattern, 0.6, 2, 15, 10);
+
+            assert_ne!(cells(&first), cells(&second), "{pattern:?}");
+        }
+    }
+
+    #[test]
+    fn should_keep_every_block_inside_the_grid_once() {
+        for pattern in PATTERNS {
+            for (columns, rows) in [(15, 10), (4, 3), (1, 1), (8, 20)] {
+                let blocks = generate(pattern, 1.0, 7, columns, rows);
+
+                let mut positions: Vec<_> = blocks
+                    .iter()
+                    .map(|block| (block.column, block.row))
+                    .collect();
+                assert!(positions
+                    .iter()
+                    .all(|(column, row)| *column < columns && *row < rows));
+                positions.dedup();
+                assert_eq!(positions.len(), blocks.len(), "{pattern:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn should_follow_density() {
+        assert_eq!(generate(Pattern::Noise, 0.0, 3, 15, 10).len(), 1);
+        assert_eq!(generate(Pattern::Noise, 1.0, 3, 15, 10).len(), 150);
+
+        let sparse = generate(Pattern::Noise, 0.2, 3, 15, 10).len();
+        let dense = generate(Pattern::Noise, 0.8, 3, 15, 10).len();
+        assert!(sparse < dense);
+    }
+
+    #[test]
+    fn should_always_leave_a_block_to_break() {
+        for pattern in PATTERNS {
+            for (columns, rows) in [(15, 10), (3, 3), (1, 1)] {
+                let blocks = generate(pattern, 0.0, 13, columns, rows);
+
+                assert!(
+                    blocks.iter().any(|block| block.kind.is_breakable()),
+                    "{pattern:?} {columns}x{rows}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn should_mirror_symmetric_layout() {
+        let blocks = generate(Pattern::Symmetric, 0.5, 11, 15, 10);
+
+        for block in &blocks {
+            let mirrored = 14 - block.column;
+            assert!(blocks
+                .iter()
+                .any(|other| other.row == block.row && other.column == mirrored));
+        }
+    }
+
+    #[test]
+    fn should_widen_pyramid_towards_the_bottom() {
+        let blocks = generate(Pattern::Pyramid, 1.0, 5, 15, 10);
+
+        let widths: Vec<_> = (0..10)
+            .map(|row| blocks.iter().filter(|block| block.row == row).count())
+            .collect();
+
+        assert!(widths.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(widths[9], 15);
+        assert!(widths[0] < 15);
+    }
+
+    #[test]
+    fn should_leave_paths_through_maze() {
+        let blocks = generate(Pattern::Maze, 1.0, 9, 15, 9);
+
+        assert!(blocks.len() < 15 * 9);
+        assert!(!blocks
+            .iter()
+            .any(|block| block.column % 2 == 0 && block.row % 2 == 0));
+        assert!(blocks
+            .iter()
+            .any(|block| block.kind == BlockKind::Indestructible));
+    }
+}
@@ -0,0 +1,262 @@
+use bevy::{input::InputSystem, prelude::*, utils::HashMap};
+
+use crate::{
+    config::{Config, GameConfig},
+    gamepad::ActiveGamepad,
+};
+
+/// Something the player can do, independent of the keys or buttons bound to it.
+///
+/// Systems read `Res<Input<Action>>` instead of raw input, so tests can press actions directly.
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    Launch,
+    /// Starts the game and moves on from messages.
+    Confirm,
+    Pause,
+    ToggleInspector,
+}
+
+/// Key or button an [`Action`] can be bound to.
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    /// Button of the [`ActiveGamepad`].
+    Gamepad(GamepadButtonType),
+}
+
+/// Bindings of every action, from the `input` section of the config.
+#[derive(serde::Deserialize, Debug, PartialEq)]
+#[serde(transparent)]
+pub struct InputBindings(pub HashMap<Action, Vec<Binding>>);
+
+/// The bindings the shipped config starts out with.
+impl Default for InputBindings {
+    fn default() -> Self {
+        Self(HashMap::from([
+            (
+                Action::MoveLeft,
+                vec![
+                    Binding::Key(KeyCode::Left),
+                    Binding::Key(KeyCode::A),
+                    Binding::Gamepad(GamepadButtonType::DPadLeft),
+                ],
+            ),
+            (
+                Action::MoveRight,
+                vec![
+                    Binding::Key(KeyCode::Right),
+                    Binding::Key(KeyCode::D),
+                    Binding::Gamepad(GamepadButtonType::DPadRight),
+                ],
+            ),
+            (
+                Action::Launch,
+                vec![
+                    Binding::Mouse(MouseButton::Left),
+                    Binding::Gamepad(GamepadButtonType::South),
+                ],
+            ),
+            (
+                Action::Confirm,
+                vec![
+                    Binding::Key(KeyCode::Return),
+                    Binding::Gamepad(GamepadButtonType::Start),
+                ],
+            ),
+            (
+                Action::Pause,
+                vec![
+                    Binding::Key(KeyCode::Escape),
+                    Binding::Key(KeyCode::P),
+                    Binding::Gamepad(GamepadButtonType::Select),
+                ],
+            ),
+            (Action::ToggleInspector, vec![Binding::Key(KeyCode::Slash)]),
+        ]))
+    }
+}
+
+pub struct ActionPlugin;
+
+impl Plugin for ActionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Input<Action>>().add_systems(
+            PreUpdate,
+            update_actions
+                .after(InputSystem)
+                .run_if(resource_exists::<GameConfig>()),
+        );
+    }
+}
+
+fn update_actions(
+    mut actions: ResMut<Input<Action>>,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    gamepad: Option<Res<ActiveGamepad>>,
+    game_config: Res<GameConfig>,
+    assets: Res<Assets<Config>>,
+) {
+    let Some(config) = assets.get(&game_config.config) else {
+        return;
+    };
+
+    let is_pressed = |binding: &Binding| match *binding {
+        Binding::Key(key) => keys.pressed(key),
+        Binding::Mouse(button) => mouse_buttons.pressed(button),
+        Binding::Gamepad(button_type) => gamepad.as_deref().is_some_and(|gamepad| {
+            gamepad_buttons.pressed(GamepadButton::new(gamepad.0, button_type))
+        }),
+    };
+
+    actions.clear();
+    for (action, bindings) in &config.input.0 {
+        if bindings.iter().any(is_pressed) {
+            actions.press(*action);
+        } else {
+            actions.release(*action);
+        }
+    }
+}
+
+/// Run condition that flips between `true` and `false` every time `action` is pressed, starting
+/// out as `default`.
+pub fn action_toggle_active(
+    default: bool,
+    action: Action,
+) -> impl FnMut(Res<Input<Action>>) -> bool {
+    let mut active = default;
+    move |actions: Res<Input<Action>>| {
+        active ^= actions.just_pressed(action);
+        active
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::insert_test_config;
+
+    use super::*;
+
+    fn action_app() -> App {
+        let mut app = App::new();
+        insert_test_config(&mut app);
+        app.add_plugins(ActionPlugin)
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<Input<MouseButton>>()
+            .init_resource::<Input<GamepadButton>>();
+        app
+    }
+
+    #[test]
+    fn should_read_bindings_from_config() {
+        let config: Config = serde_yaml::from_str(include_str!("../assets/game.config.yaml"))
+            .expect("game config should be valid");
+
+        assert_eq!(
+            config.input.0[&Action::MoveLeft],
+            vec![
+                Binding::Key(KeyCode::Left),
+                Binding::Key(KeyCode::A),
+                Binding::Gamepad(GamepadButtonType::DPadLeft)
+            ]
+        );
+        assert_eq!(
+            config.input.0[&Action::Launch],
+            vec![
+                Binding::Mouse(MouseButton::Left),
+                Binding::Gamepad(GamepadButtonType::South)
+            ]
+        );
+    }
+
+    #[test]
+    fn should_fall_back_to_default_bindings_without_input_section() {
+        let yaml = include_str!("../assets/game.config.yaml");
+        let start = yaml
+            .find("\ninput:")
+            .expect("game config should bind input");
+        let end = start + yaml[start + 1..].find("\npower_ups:").unwrap() + 1;
+        let config: Config = serde_yaml::from_str(&format!("{}{}", &yaml[..start], &yaml[end..]))
+            .expect("game config without input section should be valid");
+
+        assert_eq!(config.input, InputBindings::default());
+    }
+
+    #[test]
+    fn should_press_action_while_any_binding_is_pressed() {
+        let mut app = action_app();
+
+        app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::A);
+        app.update();
+        let actions = app.world.resource::<Input<Action>>();
+        assert!(actions.just_pressed(Action::MoveLeft));
+        assert!(!actions.pressed(Action::MoveRight));
+
+        app.update();
+        let actions = app.world.resource::<Input<Action>>();
+        assert!(actions.pressed(Action::MoveLeft));
+        assert!(!actions.just_pressed(Action::MoveLeft));
+
+        app.world
+            .resource_mut::<Input<KeyCode>>()
+            .release(KeyCode::A);
+        app.update();
+        assert!(app
+            .world
+            .resource::<Input<Action>>()
+            .just_released(Action::MoveLeft));
+    }
+
+    #[test]
+    fn should_only_read_buttons_of_active_gamepad() {
+        let mut app = action_app();
+        let south = |id| GamepadButton::new(Gamepad::new(id), GamepadButtonType::South);
+
+        app.world
+            .resource_mut::<Input<GamepadButton>>()
+            .press(south(1));
+        app.update();
+        assert!(!app
+            .world
+            .resource::<Input<Action>>()
+            .pressed(Action::Launch));
+
+        app.insert_resource(ActiveGamepad(Gamepad::new(1)));
+        app.update();
+        assert!(app
+            .world
+            .resource::<Input<Action>>()
+            .pressed(Action::Launch));
+    }
+
+    #[test]
+    fn should_toggle_when_action_is_pressed() {
+        let mut app = App::new();
+        app.init_resource::<Input<Action>>()
+            .init_resource::<Toggled>()
+            .add_systems(
+                Update,
+                (|mut toggled: ResMut<Toggled>| toggled.0 += 1)
+                    .run_if(action_toggle_active(true, Action::ToggleInspector)),
+            );
+
+        app.update();
+        app.world
+            .resource_mut::<Input<Action>>()
+            .press(Action::ToggleInspector);
+        app.update();
+        app.world.resource_mut::<Input<Action>>().clear();
+        app.update();
+
+        assert_eq!(app.world.resource::<Toggled>().0, 1);
+    }
+
+    #[derive(Resource, Default)]
+    struct Toggled(u32);
+}
@@ -0,0 +1,309 @@
+use bevy::prelude::*;
+
+use crate::{
+    block::{Block, BlockSet, DamageBlock, DestroyedBy},
+    collision::Aabb,
+    config::{Config, GameConfig},
+    effect::effect_active,
+    game::{despawn_with_component, AppState, BoundingBox, PlayState, SimulationSet},
+    input::Action,
+    paddle::{Dimensions, Paddle, Speed},
+    powerup::PowerUpKind,
+    spatial::SpatialGrid,
+};
+
+/// Lets the paddle shoot blocks while the [`PowerUpKind::Laser`] effect is active.
+pub struct LaserPlugin;
+
+impl Plugin for LaserPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                move_projectiles.in_set(SimulationSet::Physics),
+                hit_blocks_with_projectiles
+                    .in_set(SimulationSet::Gameplay)
+                    .before(BlockSet::Hit),
+                fire_lasers.in_set(SimulationSet::Gameplay).run_if(
+                    in_state(PlayState::BallInGame).and_then(effect_active(PowerUpKind::Laser)),
+                ),
+            )
+                .distributive_run_if(in_state(AppState::Playing)),
+        )
+        .add_systems(
+            OnExit(AppState::Playing),
+            despawn_with_component::<Projectile>,
+        );
+    }
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub struct LaserConfig {
+    /// In seconds.
+    pub duration: f32,
+    /// Pairs of projectiles fired per second while the launch action is held.
+    pub rate_of_fire: f32,
+    /// In units per second.
+    pub projectile_speed: f32,
+    pub projectile_width: f32,
+    pub projectile_height: f32,
+}
+
+/// Shot fired straight up from one of the paddle's edges.
+///
+/// It has no [`Dimensions`], so balls fly right through it.
+#[derive(Component, Debug)]
+pub struct Projectile {
+    pub size: Vec2,
+}
+
+/// Fires a pair of projectiles whenever the launch action is held and the previous pair was fired
+/// long enough ago.
+fn fire_lasers(
+    mut commands: Commands,
+    mut cooldown: Local<f32>,
+    actions: Res<Input<Action>>,
+    paddle: Query<(&GlobalTransform, &Dimensions), With<Paddle>>,
+    game_config: Res<GameConfig>,
+    assets: Res<Assets<Config>>,
+    time: Res<FixedTime>,
+) {
+    let Some(config) = assets.get(&game_config.config) else {
+        panic!("game config could not be loaded")
+    };
+    let config = &config.power_ups.laser;
+
+    let delta = time.period.as_secs_f32();
+    if *cooldown > 0.0 || !actions.pressed(Action::Launch) {
+        *cooldown = (*cooldown - delta).max(0.0);
+        return;
+    }
+    let Ok((transform, dimensions)) = paddle.get_single() else {
+        return;
+    };
+    *cooldown = 1.0 / config.rate_of_fire - delta;
+
+    let size = Vec2::new(config.projectile_width, config.projectile_height);
+    let center = transform.translation().truncate();
+    let edge = dimensions.0.x / 2.0 - size.x / 2.0;
+    let y = center.y + dimensions.0.y / 2.0 + size.y / 2.0;
+    for x in [center.x - edge, center.x + edge] {
+        commands.spawn((
+            Projectile { size },
+            Speed(Vec2::Y * config.projectile_speed),
+            SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(size),
+                    color: Color::YELLOW,
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(x, y, 0.5),
+                ..Default::default()
+            },
+            Name::from("Projectile"),
+        ));
+    }
+}
+
+fn move_projectiles(
+    mut projectiles: Query<(&mut Transform, &Speed), With<Projectile>>,
+    time: Res<FixedTime>,
+) {
+    let delta = time.period.as_secs_f32();
+    for (mut transform, speed) in &mut projectiles {
+        transform.translation += speed.0.extend(0.0) * delta;
+    }
+}
+
+/// Damages the lowest block each projectile passed through in the last tick, and removes the
+/// projectiles that hit a block or reached the top of the playfield.
+fn hit_blocks_with_projectiles(
+    mut commands: Commands,
+    projectiles: Query<(Entity, &Transform, &Speed, &Projectile)>,
+    blocks: Query<(Entity, &GlobalTransform, &Dimensions), With<Block>>,
+    bounding_box: Query<(&GlobalTransform, &Dimensions), With<BoundingBox>>,
+    grid: Res<SpatialGrid>,
+    mut damage: EventWriter<DamageBlock>,
+    time: Res<FixedTime>,
+) {
+    let top = bounding_box
+        .get_single()
+        .map_or(f32::INFINITY, |(transform, dimensions)| {
+            transform.translation().y + dimensions.0.y / 2.0
+        });
+    let delta = time.period.as_secs_f32();
+
+    for (entity, transform, speed, projectile) in &projectiles {
+        let rect = Rect::from_center_size(transform.translation.truncate(), projectile.size);
+        // Everything between the previous and the current position, so no block is skipped.
+        let path = rect.union(Rect {
+            min: rect.min - speed.0 * delta,
+            max: rect.max - speed.0 * delta,
+        });
+
+        let hit = grid
+            .query(Aabb::new(path.center(), path.size()))
+            .into_iter()
+            .filter_map(|(entity, _)| blocks.get(entity).ok())
+            .map(|(block, transform, dimensions)| {
+                let aabb = Rect::from_center_size(transform.translation().truncate(), dimensions.0);
+                (block, aabb)
+            })
+            .filter(|(_, aabb)| !path.intersect(*aabb).is_empty())
+            .min_by(|(_, a), (_, b)| a.min.y.total_cmp(&b.min.y));
+
+        if let Some((block, _)) = hit {
+            damage.send(DamageBlock {
+                block,
+                by: DestroyedBy::Laser(entity),
+            });
+            commands.entity(entity).despawn_recursive();
+        } else if rect.max.y >= top {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{config::insert_test_config, spatial::update_spatial_grid};
+
+    use super::*;
+
+    fn laser_app() -> App {
+        let mut app = App::new();
+        insert_test_config(&mut app);
+        app.add_event::<DamageBlock>()
+            .insert_resource(FixedTime::new_from_secs(0.25))
+            .init_resource::<Input<Action>>()
+            .insert_resource(SpatialGrid::new(64.0))
+            .add_systems(
+                Update,
+                (
+                    update_spatial_grid,
+                    move_projectiles,
+                    hit_blocks_with_projectiles,
+                )
+                    .chain(),
+            );
+        app.world.spawn((
+            GlobalTransform::default(),
+            Dimensions(Vec2::new(800.0, 600.0)),
+            BoundingBox,
+        ));
+        app.world.spawn((
+            GlobalTransform::from_xyz(0.0, -280.0, 0.0),
+            Dimensions(Vec2::new(100.0, 20.0)),
+            Paddle {
+                max_deflection_angle: 1.0,
+                english: 0.0,
+            },
+        ));
+        app
+    }
+
+    fn spawn_projectile(app: &mut App, position: Vec2) -> Entity {
+        app.world
+            .spawn((
+                Projectile {
+                    size: Vec2::new(4.0, 10.0),
+                },
+                Speed(Vec2::new(0.0, 400.0)),
+                Transform::from_translation(position.extend(0.0)),
+            ))
+            .id()
+    }
+
+    fn spawn_block(app: &mut App, position: Vec2) -> Entity {
+        app.world
+            .spawn((
+                Block,
+                GlobalTransform::from_translation(position.extend(0.0)),
+                Dimensions(Vec2::new(60.0, 20.0)),
+            ))
+            .id()
+    }
+
+    fn damaged(app: &App) -> Vec<DamageBlock> {
+        let events = app.world.resource::<Events<DamageBlock>>();
+        events.get_reader().iter(events).copied().collect()
+    }
+
+    fn projectiles(app: &mut App) -> Vec<Vec2> {
+        app.world
+            .query_filtered::<&Transform, With<Projectile>>()
+            .iter(&app.world)
+            .map(|transform| transform.translation.truncate())
+            .collect()
+    }
+
+    #[test]
+    fn should_fire_pairs_from_paddle_edges_at_rate_of_fire() {
+        let mut app = laser_app();
+        app.add_systems(Update, fire_lasers.before(move_projectiles));
+        app.world
+            .resource_mut::<Assets<Config>>()
+            .iter_mut()
+            .for_each(|(_, config)| {
+                config.power_ups.laser = LaserConfig {
+                    duration: 10.0,
+                    rate_of_fire: 2.0,
+                    projectile_speed: 0.0,
+                    projectile_width: 4.0,
+                    projectile_height: 10.0,
+                }
+            });
+
+        app.update();
+        assert!(projectiles(&mut app).is_empty());
+
+        app.world
+            .resource_mut::<Input<Action>>()
+            .press(Action::Launch);
+        app.update();
+        let mut fired = projectiles(&mut app);
+        fired.sort_by(|a, b| a.x.total_cmp(&b.x));
+        assert_eq!(fired, [Vec2::new(-48.0, -265.0), Vec2::new(48.0, -265.0)]);
+
+        app.update();
+        assert_eq!(projectiles(&mut app).len(), 2);
+        app.update();
+        assert_eq!(projectiles(&mut app).len(), 4);
+    }
+
+    #[test]
+    fn should_damage_lowest_block_in_the_way() {
+        let mut app = laser_app();
+        let projectile = spawn_projectile(&mut app, Vec2::new(0.0, 0.0));
+        let upper = spawn_block(&mut app, Vec2::new(0.0, 90.0));
+        let lower = spawn_block(&mut app, Vec2::new(10.0, 60.0));
+        spawn_block(&mut app, Vec2::new(100.0, 60.0));
+
+        app.update();
+
+        assert_eq!(
+            damaged(&app),
+            [DamageBlock {
+                block: lower,
+                by: DestroyedBy::Laser(projectile),
+            }]
+        );
+        assert!(app.world.get_entity(projectile).is_none());
+        assert!(app.world.get_entity(upper).is_some());
+    }
+
+    #[test]
+    fn should_remove_projectile_at_top_of_bounding_box() {
+        let mut app = laser_app();
+        let projectile = spawn_projectile(&mut app, Vec2::new(0.0, 100.0));
+
+        app.update();
+        assert!(app.world.get_entity(projectile).is_some());
+
+        for _ in 0..2 {
+            app.update();
+        }
+        assert!(app.world.get_entity(projectile).is_none());
+        assert!(damaged(&app).is_empty());
+    }
+}
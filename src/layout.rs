@@ -0,0 +1,209 @@
+use std::fmt;
+
+use bevy::prelude::*;
+
+use crate::config::BlockConfig;
+
+/// Where the cells of the block grid are on a playfield centered on the origin.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridLayout {
+    /// Center of the cell in the first column and row.
+    first: Vec2,
+    /// Distance between the centers of neighbouring cells.
+    step: Vec2,
+    block_size: Vec2,
+    pub columns: u32,
+    pub rows: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayoutError {
+    NoCells { columns: u32, rows: u32 },
+    TooWide { width: f32, available: f32 },
+    TooTall { height: f32, available: f32 },
+}
+
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutError::NoCells { columns, rows } => {
+                write!(f, "a grid of {columns}x{rows} blocks has no cells")
+            }
+            LayoutError::TooWide { width, available } => write!(
+                f,
+                "the block grid is {width} wide, but the playfield is only {available} wide"
+            ),
+            LayoutError::TooTall { height, available } => write!(
+                f,
+                "the block grid needs {height} from the top, but the playfield is only \
+                 {available} tall"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LayoutError {}
+
+impl GridLayout {
+    /// Centers the grid horizontally on a `playfield` of the given size and places its top row
+    /// `offset_from_top` below the top of the playfield.
+    pub fn new(config: &BlockConfig, playfield: Vec2) -> Result<Self, LayoutError> {
+        if config.columns == 0 || config.rows == 0 {
+            return Err(LayoutError::NoCells {
+                columns: config.columns,
+                rows: config.rows,
+            });
+        }
+
+        let block_size = Vec2::new(config.width, config.height);
+        let step = block_size + Vec2::new(config.horizontal_offset, config.vertical_offset);
+        let size = Vec2::new(
+            step.x * (config.columns - 1) as f32 + block_size.x,
+            step.y * (config.rows - 1) as f32 + block_size.y,
+        );
+
+        if size.x > playfield.x {
+            return Err(LayoutError::TooWide {
+                width: size.x,
+                available: playfield.x,
+            });
+        }
+        if config.offset_from_top + size.y > playfield.y {
+            return Err(LayoutError::TooTall {
+                height: config.offset_from_top + size.y,
+                available: playfield.y,
+            });
+        }
+
+        let top_left = Vec2::new(-size.x / 2.0, playfield.y / 2.0 - config.offset_from_top);
+        Ok(Self {
+            first: top_left + Vec2::new(block_size.x, -block_size.y) / 2.0,
+            step: Vec2::new(step.x, -step.y),
+            block_size,
+            columns: config.columns,
+            rows: config.rows,
+        })
+    }
+
+    /// Distance between the tops of neighbouring rows.
+    pub fn row_step(&self) -> f32 {
+        -self.step.y
+    }
+
+    /// Rectangle of the block in `column` and `row`, with rows counted from the top.
+    pub fn cell(&self, column: u32, row: u32) -> Rect {
+        let center = self.first + self.step * Vec2::new(column as f32, row as f32);
+        Rect::from_center_size(center, self.block_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block_config() -> BlockConfig {
+        BlockConfig {
+            width: 60.0,
+            height: 15.0,
+            horizontal_offset: 10.0,
+            vertical_offset: 5.0,
+            rows: 10,
+            columns: 15,
+            offset_from_top: 120.0,
+        }
+    }
+
+    fn all_cells(layout: &GridLayout) -> Vec<Rect> {
+        (0..layout.rows)
+            .flat_map(|row| (0..layout.columns).map(move |column| layout.cell(column, row)))
+            .collect()
+    }
+
+    #[test]
+    fn should_center_grid_horizontally_on_several_playfields() {
+        for playfield in [
+            Vec2::new(1280.0, 720.0),
+            Vec2::new(1040.0, 315.0),
+            Vec2::new(1921.0, 1080.0),
+        ] {
+            let layout = GridLayout::new(&block_config(), playfield).unwrap();
+
+            let left = layout.cell(0, 0).min.x;
+            let right = layout.cell(14, 0).max.x;
+            assert_eq!(left, -right, "{playfield}");
+            assert_eq!(right - left, 15.0 * 60.0 + 14.0 * 10.0, "{playfield}");
+            assert_eq!(layout.cell(0, 0).max.y, playfield.y / 2.0 - 120.0);
+        }
+    }
+
+    #[test]
+    fn should_honor_configured_rows_and_columns() {
+        let layout = GridLayout::new(&block_config(), Vec2::new(1280.0, 720.0)).unwrap();
+
+        let cells = all_cells(&layout);
+
+        assert_eq!(cells.len(), 15 * 10);
+        assert_eq!(layout.cell(1, 0).min.x - layout.cell(0, 0).max.x, 10.0);
+        assert_eq!(layout.cell(0, 0).min.y - layout.cell(0, 1).max.y, 5.0);
+        assert!(cells
+            .iter()
+            .all(|cell| cell.size() == Vec2::new(60.0, 15.0)));
+    }
+
+    #[test]
+    fn should_keep_every_block_inside_the_playfield() {
+        let playfield = Vec2::new(1040.0, 315.0);
+        let bounds = Rect::from_center_size(Vec2::ZERO, playfield);
+        let layout = GridLayout::new(&block_config(), playfield).unwrap();
+
+        for cell in all_cells(&layout) {
+            assert_eq!(bounds.union(cell), bounds);
+        }
+    }
+
+    #[test]
+    fn should_fail_when_grid_is_wider_than_playfield() {
+        let error = GridLayout::new(&block_config(), Vec2::new(800.0, 600.0)).unwrap_err();
+
+        assert_eq!(
+            error,
+            LayoutError::TooWide {
+                width: 1040.0,
+                available: 800.0
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "the block grid is 1040 wide, but the playfield is only 800 wide"
+        );
+    }
+
+    #[test]
+    fn should_fail_when_grid_is_taller_than_playfield() {
+        let error = GridLayout::new(&block_config(), Vec2::new(1280.0, 300.0)).unwrap_err();
+
+        assert_eq!(
+            error,
+            LayoutError::TooTall {
+                height: 315.0,
+                available: 300.0
+            }
+        );
+    }
+
+    #[test]
+    fn should_fail_without_cells() {
+        let config = BlockConfig {
+            rows: 0,
+            ..block_config()
+        };
+
+        assert_eq!(
+            GridLayout::new(&config, Vec2::new(1280.0, 720.0)),
+            Err(LayoutError::NoCells {
+                columns: 15,
+                rows: 0
+            })
+        );
+    }
+}
@@ -0,0 +1,586 @@
+use std::fmt;
+
+use bevy::{
+    ecs::system::SystemParam,
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+    utils::HashMap,
+};
+use bevy_asset_loader::prelude::*;
+
+use crate::{
+    block::{full_grid, Block},
+    config::{Config, GameConfig, LevelSource},
+    game::AppState,
+    generator::{generate, random_seed},
+    input::Action,
+};
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnExit(AppState::Menu), reset_current_level)
+            .add_systems(
+                Update,
+                check_level_complete.run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(OnEnter(AppState::LevelComplete), advance_level)
+            .add_systems(
+                Update,
+                continue_to_next_level.run_if(in_state(AppState::LevelComplete)),
+            )
+            .add_systems(Update, return_to_menu.run_if(in_state(AppState::Victory)));
+    }
+}
+
+/// Index of the level being played in [`Config::levels`].
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CurrentLevel(pub usize);
+
+/// Loaded levels together with the one being played.
+#[derive(SystemParam)]
+pub struct Levels<'w> {
+    level_assets: Res<'w, LevelAssets>,
+    levels: Res<'w, Assets<Level>>,
+    current_level: Res<'w, CurrentLevel>,
+}
+
+impl Levels<'_> {
+    /// Blocks of the level being played, along with the seed they were generated from if they
+    /// were generated.
+    pub fn current_blocks(&self, config: &Config) -> (Vec<LevelBlock>, Option<u64>) {
+        let (columns, rows) = (config.block.columns, config.block.rows);
+        match config.levels.get(self.current_level.0) {
+            Some(LevelSource::File(path)) => (self.file_blocks(path, columns, rows), None),
+            Some(LevelSource::Generated(generator)) => {
+                let seed = generator.seed.unwrap_or_else(random_seed);
+                let blocks = generate(generator.pattern, generator.density, seed, columns, rows);
+                (blocks, Some(seed))
+            }
+            Some(LevelSource::Progressive(progressive)) => {
+                let generator = progressive.generator;
+                let seed = generator.seed.unwrap_or_else(random_seed);
+                let rows = progressive.rows.min(rows);
+                let blocks = generate(generator.pattern, generator.density, seed, columns, rows);
+                (blocks, Some(seed))
+            }
+            Some(LevelSource::Grid) | None => (full_grid(&config.block), None),
+        }
+    }
+
+    pub fn current_source<'a>(&self, config: &'a Config) -> Option<&'a LevelSource> {
+        config.levels.get(self.current_level.0)
+    }
+
+    fn file_blocks(&self, path: &str, columns: u32, rows: u32) -> Vec<LevelBlock> {
+        let Some(level) = self
+            .level_assets
+            .levels
+            .get(path)
+            .and_then(|level| self.levels.get(level))
+        else {
+            panic!("level {path} could not be loaded")
+        };
+        level
+            .blocks(columns, rows)
+            .unwrap_or_else(|error| panic!("level {path} is invalid: {error}"))
+    }
+}
+
+/// Number of levels in a game, including the grid that is played when no level is configured.
+pub fn level_count(config: &Config) -> usize {
+    config.levels.len().max(1)
+}
+
+/// Every level in `assets/levels`, keyed by their path, e.g. `levels/classic.level.yaml`.
+#[derive(AssetCollection, Resource, Debug)]
+pub struct LevelAssets {
+    #[asset(path = "levels", collection(typed, mapped))]
+    pub levels: HashMap<String, Handle<Level>>,
+}
+
+/// Block layout loaded from a `*.level.yaml` file.
+///
+/// Blocks can be listed one by one in `blocks`, drawn in `layout` with one character per grid
+/// cell, or both. Characters in `layout` are looked up in `legend`, `.` and spaces leave the cell
+/// empty.
+#[derive(serde::Deserialize, TypeUuid, TypePath, Debug, Default)]
+#[uuid = "0d7c6a4e-3f5b-4a2e-9c59-7d1b8e0f4a21"]
+#[serde(deny_unknown_fields)]
+pub struct Level {
+    #[serde(default)]
+    pub blocks: Vec<LevelBlock>,
+    #[serde(default)]
+    pub layout: Option<String>,
+    #[serde(default)]
+    pub legend: HashMap<char, BlockStyle>,
+}
+
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct LevelBlock {
+    pub column: u32,
+    /// Counted from the top.
+    pub row: u32,
+    #[serde(default)]
+    pub kind: BlockKind,
+    pub color: Color,
+    #[serde(default = "default_hit_points")]
+    pub hit_points: u32,
+}
+
+/// What a character of [`Level::layout`] stands for.
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct BlockStyle {
+    #[serde(default)]
+    pub kind: BlockKind,
+    pub color: Color,
+    #[serde(default = "default_hit_points")]
+    pub hit_points: u32,
+}
+
+#[derive(serde::Deserialize, Component, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlockKind {
+    #[default]
+    Normal,
+    /// Can't be destroyed and doesn't have to be for the level to be complete.
+    Indestructible,
+    /// Also destroys every block up to `radius` cells away when it breaks.
+    Explosive { radius: u32 },
+    /// Hidden until the first hit, which only reveals it.
+    Invisible,
+}
+
+impl BlockKind {
+    pub fn is_breakable(&self) -> bool {
+        *self != BlockKind::Indestructible
+    }
+}
+
+fn default_hit_points() -> u32 {
+    1
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LevelError {
+    UnknownSymbol {
+        symbol: char,
+        column: u32,
+        row: u32,
+    },
+    OutsideGrid {
+        column: u32,
+        row: u32,
+        columns: u32,
+        rows: u32,
+    },
+    DuplicateCell {
+        column: u32,
+        row: u32,
+    },
+    NoHitPoints {
+        column: u32,
+        row: u32,
+    },
+    Empty,
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelError::UnknownSymbol {
+                symbol,
+                column,
+                row,
+            } => write!(
+                f,
+                "layout uses '{symbol}' at column {column}, row {row}, but the legend does not \
+                 define it"
+            ),
+            LevelError::OutsideGrid {
+                column,
+                row,
+                columns,
+                rows,
+            } => write!(
+                f,
+                "block at column {column}, row {row} is outside of the {columns}x{rows} grid"
+            ),
+            LevelError::DuplicateCell { column, row } => {
+                write!(f, "more than one block at column {column}, row {row}")
+            }
+            LevelError::NoHitPoints { column, row } => {
+                write!(f, "block at column {column}, row {row} has 0 hit points")
+            }
+            LevelError::Empty => write!(f, "level has no blocks"),
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+impl Level {
+    /// Returns every block of the level, ordered by row and column, after checking that they all
+    /// fit into a grid of `columns` by `rows` cells.
+    pub fn blocks(&self, columns: u32, rows: u32) -> Result<Vec<LevelBlock>, LevelError> {
+        let mut blocks = self.blocks.clone();
+        blocks.extend(self.layout_blocks()?);
+
+        if blocks.is_empty() {
+            return Err(LevelError::Empty);
+        }
+
+        for block in &blocks {
+            let (column, row) = (block.column, block.row);
+            if column >= columns || row >= rows {
+                return Err(LevelError::OutsideGrid {
+                    column,
+                    row,
+                    columns,
+                    rows,
+                });
+            }
+            if block.hit_points == 0 {
+                return Err(LevelError::NoHitPoints { column, row });
+            }
+        }
+
+        blocks.sort_by_key(|block| (block.row, block.column));
+        if let Some(pair) = blocks
+            .windows(2)
+            .find(|pair| (pair[0].column, pair[0].row) == (pair[1].column, pair[1].row))
+        {
+            return Err(LevelError::DuplicateCell {
+                column: pair[0].column,
+                row: pair[0].row,
+            });
+        }
+
+        Ok(blocks)
+    }
+
+    fn layout_blocks(&self) -> Result<Vec<LevelBlock>, LevelError> {
+        let Some(layout) = &self.layout else {
+            return Ok(Vec::new());
+        };
+
+        let mut blocks = Vec::new();
+        for (row, line) in layout.lines().enumerate() {
+            for (column, symbol) in line.chars().enumerate() {
+                let (column, row) = (column as u32, row as u32);
+                if symbol == '.' || symbol == ' ' {
+                    continue;
+                }
+
+                let Some(style) = self.legend.get(&symbol) else {
+                    return Err(LevelError::UnknownSymbol {
+                        symbol,
+                        column,
+                        row,
+                    });
+                };
+                blocks.push(LevelBlock {
+                    column,
+                    row,
+                    kind: style.kind,
+                    color: style.color,
+                    hit_points: style.hit_points,
+                });
+            }
+        }
+
+        Ok(blocks)
+    }
+}
+
+fn reset_current_level(mut commands: Commands) {
+    commands.insert_resource(CurrentLevel::default());
+}
+
+/// Finishes the level once every block that can be destroyed is gone.
+fn check_level_complete(
+    blocks: Query<&BlockKind, With<Block>>,
+    current_level: Res<CurrentLevel>,
+    game_config: Res<GameConfig>,
+    assets: Res<Assets<Config>>,
+    mut state: ResMut<NextState<AppState>>,
+) {
+    let Some(config) = assets.get(&game_config.config) else {
+        panic!("game config could not be loaded")
+    };
+
+    if blocks.iter().any(BlockKind::is_breakable) {
+        return;
+    }
+
+    if current_level.0 + 1 < level_count(config) {
+        state.set(AppState::LevelComplete);
+    } else {
+        state.set(AppState::Victory);
+    }
+}
+
+fn advance_level(mut current_level: ResMut<CurrentLevel>) {
+    current_level.0 += 1;
+}
+
+fn continue_to_next_level(actions: Res<Input<Action>>, mut state: ResMut<NextState<AppState>>) {
+    if actions.just_pressed(Action::Confirm) {
+        state.set(AppState::Playing)
+    }
+}
+
+fn return_to_menu(actions: Res<Input<Action>>, mut state: ResMut<NextState<AppState>>) {
+    if actions.just_pressed(Action::Confirm) {
+        state.set(AppState::Menu)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::insert_test_config;
+
+    use super::*;
+
+    fn parse(yaml: &str) -> Level {
+        serde_yaml::from_str(yaml).expect("level should parse")
+    }
+
+    #[test]
+    fn should_read_blocks_from_layout_and_list() {
+        let level = parse(
+            "
+blocks:
+  - column: 3
+    row: 2
+    color: !Rgba { red: 1.0, green: 0.0, blue: 0.0, alpha: 1.0 }
+    hit_points: 3
+layout: |
+  #.#
+  .#
+legend:
+  '#':
+    color: !Rgba { red: 0.0, green: 1.0, blue: 0.0, alpha: 1.0 }
+",
+        );
+
+        let blocks = level.blocks(4, 3).unwrap();
+
+        let cells: Vec<_> = blocks
+            .iter()
+            .map(|block| (block.column, block.row, block.hit_points))
+            .collect();
+        assert_eq!(cells, [(0, 0, 1), (2, 0, 1), (1, 1, 1), (3, 2, 3)]);
+        assert!(blocks.iter().all(|block| block.kind == BlockKind::Normal));
+    }
+
+    #[test]
+    fn should_read_block_kinds() {
+        let level = parse(
+            "
+layout: |
+  XEI
+legend:
+  X:
+    kind: Indestructible
+    color: !Rgba { red: 0.5, green: 0.5, blue: 0.5, alpha: 1.0 }
+  E:
+    kind: !Explosive { radius: 2 }
+    color: !Rgba { red: 1.0, green: 0.0, blue: 0.0, alpha: 1.0 }
+  I:
+    kind: Invisible
+    color: !Rgba { red: 1.0, green: 1.0, blue: 1.0, alpha: 1.0 }
+",
+        );
+
+        let kinds: Vec<_> = level
+            .blocks(3, 1)
+            .unwrap()
+            .iter()
+            .map(|block| block.kind)
+            .collect();
+
+        assert_eq!(
+            kinds,
+            [
+                BlockKind::Indestructible,
+                BlockKind::Explosive { radius: 2 },
+                BlockKind::Invisible
+            ]
+        );
+    }
+
+    #[test]
+    fn should_report_symbol_missing_from_legend() {
+        let level = parse(
+            "
+layout: |
+  ##
+  #x
+legend:
+  '#':
+    color: !Rgba { red: 0.0, green: 1.0, blue: 0.0, alpha: 1.0 }
+",
+        );
+
+        let error = level.blocks(10, 10).unwrap_err();
+
+        assert_eq!(
+            error,
+            LevelError::UnknownSymbol {
+                symbol: 'x',
+                column: 1,
+                row: 1
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "layout uses 'x' at column 1, row 1, but the legend does not define it"
+        );
+    }
+
+    #[test]
+    fn should_reject_blocks_that_do_not_fit_the_grid() {
+        let level = parse(
+            "
+layout: |
+  ....#
+legend:
+  '#':
+    color: !Rgba { red: 0.0, green: 1.0, blue: 0.0, alpha: 1.0 }
+",
+        );
+
+        assert_eq!(
+            level.blocks(4, 1).unwrap_err().to_string(),
+            "block at column 4, row 0 is outside of the 4x1 grid"
+        );
+    }
+
+    #[test]
+    fn should_reject_two_blocks_in_one_cell() {
+        let level = parse(
+            "
+blocks:
+  - column: 1
+    row: 0
+    color: !Rgba { red: 1.0, green: 0.0, blue: 0.0, alpha: 1.0 }
+layout: |
+  ##
+legend:
+  '#':
+    color: !Rgba { red: 0.0, green: 1.0, blue: 0.0, alpha: 1.0 }
+",
+        );
+
+        assert_eq!(
+            level.blocks(4, 4).unwrap_err(),
+            LevelError::DuplicateCell { column: 1, row: 0 }
+        );
+    }
+
+    #[test]
+    fn should_reject_blocks_without_hit_points_and_empty_levels() {
+        let level = parse(
+            "
+blocks:
+  - column: 0
+    row: 0
+    color: !Rgba { red: 1.0, green: 0.0, blue: 0.0, alpha: 1.0 }
+    hit_points: 0
+",
+        );
+
+        assert_eq!(
+            level.blocks(4, 4).unwrap_err(),
+            LevelError::NoHitPoints { column: 0, row: 0 }
+        );
+        assert_eq!(Level::default().blocks(4, 4), Err(LevelError::Empty));
+    }
+
+    #[test]
+    fn should_build_every_configured_level() {
+        let config: Config = serde_yaml::from_str(include_str!("../assets/game.config.yaml"))
+            .expect("game config should be valid");
+        assert!(!config.levels.is_empty());
+
+        for source in &config.levels {
+            let LevelSource::File(path) = source else {
+                continue;
+            };
+            let level = parse(&std::fs::read_to_string(format!("assets/{path}")).unwrap());
+
+            if let Err(error) = level.blocks(config.block.columns, config.block.rows) {
+                panic!("level {path} is invalid: {error}");
+            }
+        }
+    }
+
+    fn progression_app(current_level: usize, kind: BlockKind) -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, LevelPlugin));
+        insert_test_config(&mut app);
+
+        app.add_state::<AppState>()
+            .insert_resource(State::new(AppState::Playing))
+            .insert_resource(CurrentLevel(current_level))
+            .insert_resource(Input::<Action>::default())
+            .add_systems(
+                Update,
+                apply_state_transition::<AppState>.after(check_level_complete),
+            );
+        app.world.spawn((Block, kind));
+
+        app
+    }
+
+    #[test]
+    fn should_complete_level_when_only_indestructible_blocks_are_left() {
+        let mut app = progression_app(0, BlockKind::Indestructible);
+
+        app.update();
+
+        assert_eq!(
+            *app.world.resource::<State<AppState>>().get(),
+            AppState::LevelComplete
+        );
+        assert_eq!(*app.world.resource::<CurrentLevel>(), CurrentLevel(1));
+    }
+
+    #[test]
+    fn should_keep_playing_while_breakable_blocks_are_left() {
+        let mut app = progression_app(0, BlockKind::Invisible);
+
+        app.update();
+
+        assert_eq!(
+            *app.world.resource::<State<AppState>>().get(),
+            AppState::Playing
+        );
+        assert_eq!(*app.world.resource::<CurrentLevel>(), CurrentLevel(0));
+    }
+
+    #[test]
+    fn should_win_after_last_level() {
+        let mut app = progression_app(0, BlockKind::Indestructible);
+        let config = app.world.resource::<GameConfig>().config.clone();
+        let last = level_count(app.world.resource::<Assets<Config>>().get(&config).unwrap()) - 1;
+        app.insert_resource(CurrentLevel(last));
+
+        app.update();
+
+        assert_eq!(
+            *app.world.resource::<State<AppState>>().get(),
+            AppState::Victory
+        );
+    }
+
+    #[test]
+    fn should_fail_to_parse_misspelled_field() {
+        let result = serde_yaml::from_str::<Level>("layuot: '#'");
+
+        assert!(result.is_err());
+    }
+}
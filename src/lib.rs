@@ -1,10 +1,25 @@
 mod ball;
 mod block;
+mod catch;
+mod collision;
 mod config;
 mod debug;
+mod effect;
 mod game;
+mod gamepad;
+mod generator;
+mod input;
+mod laser;
+mod layout;
+mod level;
 mod paddle;
-mod ui;
+mod powerup;
+mod progressive;
+mod score;
+mod spatial;
 mod stats;
+mod ui;
 
+pub use collision::{sweep_circle_aabb, Aabb};
 pub use game::GamePlugin;
+pub use spatial::SpatialGrid;
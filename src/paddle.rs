@@ -1,19 +1,56 @@
-use bevy::{prelude::*, window::PrimaryWindow};
+use bevy::{
+    prelude::*,
+    window::{CursorGrabMode, PrimaryWindow},
+};
 
 use crate::{
     config::{Config, GameConfig},
-    game::{AppState, SpawningSet},
+    debug::MousePosition,
+    game::{despawn_with_component, AppState, BoundingBox, PlayState, SimulationSet, SpawningSet},
+    gamepad::GamepadInput,
+    input::Action,
 };
 
 #[derive(Component, Deref, DerefMut, Debug, Reflect)]
 pub struct Speed(pub Vec2);
 
 #[derive(Component)]
-pub struct Paddle;
+pub struct Paddle {
+    /// Largest angle from the vertical, in radians, the ball can leave the paddle at.
+    pub max_deflection_angle: f32,
+    /// Share of the paddle's horizontal speed that is added to a ball bouncing off of it.
+    pub english: f32,
+}
+
+/// How the paddle speeds up and slows down. Its [`Speed`] is its current velocity.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct PaddleMotion {
+    pub max_speed: f32,
+    pub acceleration: f32,
+    pub friction: f32,
+}
 
 #[derive(Component, Reflect)]
 pub struct Dimensions(pub Vec2);
 
+/// Wall at the side of the playfield that the paddle stops against.
+#[derive(Component)]
+pub struct Bumper;
+
+/// How the player moves the paddle.
+#[derive(serde::Deserialize, Resource, Debug, Clone, Copy, PartialEq, Default)]
+pub enum ControlScheme {
+    /// The [`Action`]s to move left and right, or the left stick of a gamepad.
+    #[default]
+    Keyboard,
+    /// The paddle follows the cursor, moving at most `max_speed` units per second if set. It stays
+    /// put while the ball is aimed, as the cursor aims the ball then.
+    Mouse {
+        #[serde(default)]
+        max_speed: Option<f32>,
+    },
+}
+
 pub struct PaddlePlugin;
 
 impl Plugin for PaddlePlugin {
@@ -22,9 +59,28 @@ impl Plugin for PaddlePlugin {
             .register_type::<Dimensions>()
             .add_systems(
                 OnEnter(AppState::Playing),
-                spawn_paddle.in_set(SpawningSet::Paddle),
+                (spawn_paddle, spawn_bumpers).in_set(SpawningSet::Paddle),
+            )
+            .add_systems(
+                OnEnter(AppState::Playing),
+                capture_cursor.after(SpawningSet::Deferred),
             )
-            .add_systems(Update, (move_paddle).run_if(in_state(AppState::Playing)));
+            .add_systems(
+                OnExit(AppState::Playing),
+                (
+                    despawn_with_component::<Paddle>,
+                    despawn_with_component::<Bumper>,
+                    release_cursor,
+                ),
+            )
+            .add_systems(Update, resize_paddle_sprite)
+            .add_systems(
+                FixedUpdate,
+                (move_paddle, clamp_paddle)
+                    .chain()
+                    .in_set(SimulationSet::Input)
+                    .run_if(in_state(AppState::Playing)),
+            );
     }
 }
 
@@ -40,9 +96,18 @@ fn spawn_paddle(
 
     let window = window.single();
 
+    commands.insert_resource(config.paddle.control);
     commands.spawn((
-        Speed(Vec2::new(config.paddle.initial_speed, 0.0)),
-        Paddle,
+        Speed(Vec2::ZERO),
+        PaddleMotion {
+            max_speed: config.paddle.max_speed,
+            acceleration: config.paddle.acceleration,
+            friction: config.paddle.friction,
+        },
+        Paddle {
+            max_deflection_angle: config.paddle.max_deflection_angle.to_radians(),
+            english: config.paddle.english,
+        },
         Dimensions(Vec2::new(config.paddle.width, config.paddle.height)),
         SpriteBundle {
             sprite: Sprite {
@@ -61,18 +126,409 @@ fn spawn_paddle(
     ));
 }
 
+fn spawn_bumpers(
+    mut commands: Commands,
+    game_config: Res<GameConfig>,
+    assets: Res<Assets<Config>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+) {
+    let Some(config) = assets.get(&game_config.config) else {
+        panic!("game config could not be loaded")
+    };
+
+    if config.paddle.bumper_width <= 0.0 {
+        return;
+    }
+
+    let window = window.single();
+    let size = Vec2::new(
+        config.paddle.bumper_width,
+        2.0 * (config.paddle.offset_from_bottom + config.paddle.height),
+    );
+    for side in [-1.0, 1.0] {
+        commands.spawn((
+            Bumper,
+            Dimensions(size),
+            SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(size),
+                    color: config.paddle.color,
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(
+                    side * (window.width() - size.x) / 2.0,
+                    -window.height() / 2.0,
+                    0.0,
+                ),
+                ..Default::default()
+            },
+            Name::from("Bumper"),
+        ));
+    }
+}
+
 fn move_paddle(
-    mut paddle: Query<(&mut Transform, &Speed), With<Paddle>>,
-    input: Res<Input<KeyCode>>,
-    time: Res<Time>,
+    mut paddle: Query<(&mut Transform, &mut Speed, &PaddleMotion), With<Paddle>>,
+    actions: Res<Input<Action>>,
+    gamepad: GamepadInput,
+    mouse_position: Res<MousePosition>,
+    control: Res<ControlScheme>,
+    play_state: Res<State<PlayState>>,
+    time: Res<FixedTime>,
 ) {
-    let (mut transform, speed) = paddle.single_mut();
+    let (mut transform, mut speed, motion) = paddle.single_mut();
+    let delta = time.period.as_secs_f32();
+
+    if let ControlScheme::Mouse { max_speed } = *control {
+        if *play_state.get() == PlayState::ReadyToShoot {
+            speed.0.x = 0.0;
+            return;
+        }
+
+        let x = follow_cursor(
+            transform.translation.x,
+            mouse_position.world.x,
+            max_speed,
+            delta,
+        );
+        speed.0.x = (x - transform.translation.x) / delta;
+        transform.translation.x = x;
+        return;
+    }
+
+    let mut direction = gamepad.paddle_direction();
+    if actions.pressed(Action::MoveLeft) {
+        direction -= 1.0;
+    }
+    if actions.pressed(Action::MoveRight) {
+        direction += 1.0;
+    }
+
+    speed.0.x = accelerate(speed.0.x, direction.clamp(-1.0, 1.0), motion, delta);
+    transform.translation.x += delta * speed.0.x;
+}
+
+/// Paddle velocity after being pushed in `direction`, between -1 and 1, for `delta` seconds. It
+/// approaches `direction` times the top speed at the paddle's acceleration, and coasts to a halt
+/// under friction when it isn't pushed at all.
+fn accelerate(velocity: f32, direction: f32, motion: &PaddleMotion, delta: f32) -> f32 {
+    let target = direction * motion.max_speed;
+    let rate = if direction == 0.0 {
+        motion.friction
+    } else {
+        motion.acceleration
+    };
+
+    let change = rate * delta;
+    velocity + (target - velocity).clamp(-change, change)
+}
+
+type Resized = (With<Paddle>, Changed<Dimensions>);
+
+/// Keeps the sprite of the paddle as large as the paddle itself, whose width power-ups change.
+fn resize_paddle_sprite(mut paddle: Query<(&mut Sprite, &Dimensions), Resized>) {
+    for (mut sprite, dimensions) in &mut paddle {
+        sprite.custom_size = Some(dimensions.0);
+    }
+}
+
+type Wall<'a> = (&'a Transform, &'a Dimensions);
+
+/// Keeps the paddle between the walls of the bounding box and any bumpers, whatever moved it or
+/// changed its width.
+fn clamp_paddle(
+    mut paddle: Query<(&mut Transform, &Dimensions, Option<&mut Speed>), With<Paddle>>,
+    bounding_box: Query<Wall, (With<BoundingBox>, Without<Paddle>)>,
+    bumpers: Query<Wall, (With<Bumper>, Without<Paddle>)>,
+) {
+    let (Ok((mut transform, dimensions, speed)), Ok((box_transform, box_dimensions))) =
+        (paddle.get_single_mut(), bounding_box.get_single())
+    else {
+        return;
+    };
+
+    let center = box_transform.translation.x;
+    let mut left = center - box_dimensions.0.x / 2.0;
+    let mut right = center + box_dimensions.0.x / 2.0;
+    for (bumper, bumper_dimensions) in &bumpers {
+        let x = bumper.translation.x;
+        if x < center {
+            left = left.max(x + bumper_dimensions.0.x / 2.0);
+        } else {
+            right = right.min(x - bumper_dimensions.0.x / 2.0);
+        }
+    }
 
-    if input.any_pressed([KeyCode::Left, KeyCode::A]) {
-        transform.translation.x -= time.delta_seconds() * speed.0.x
+    let x = clamp_between_walls(transform.translation.x, dimensions.0.x / 2.0, left, right);
+    if x != transform.translation.x {
+        transform.translation.x = x;
+        // Running into a wall stops the paddle dead.
+        if let Some(mut speed) = speed {
+            speed.0.x = 0.0;
+        }
     }
+}
+
+/// Moves a paddle centered on `x` so it fits between `left` and `right`, centering it if it is
+/// too wide to fit at all.
+fn clamp_between_walls(x: f32, half_width: f32, left: f32, right: f32) -> f32 {
+    let (min, max) = (left + half_width, right - half_width);
+    if min > max {
+        return (left + right) / 2.0;
+    }
+    x.clamp(min, max)
+}
+
+/// Where the paddle ends up after chasing the cursor for `delta` seconds.
+fn follow_cursor(paddle_x: f32, cursor_x: f32, max_speed: Option<f32>, delta: f32) -> f32 {
+    let distance = cursor_x - paddle_x;
+    match max_speed {
+        Some(max_speed) => paddle_x + distance.clamp(-max_speed * delta, max_speed * delta),
+        None => cursor_x,
+    }
+}
+
+fn capture_cursor(
+    mut window: Query<&mut Window, With<PrimaryWindow>>,
+    control: Res<ControlScheme>,
+) {
+    if *control == ControlScheme::Keyboard {
+        return;
+    }
+
+    let mut window = window.single_mut();
+    window.cursor.visible = false;
+    window.cursor.grab_mode = CursorGrabMode::Confined;
+}
+
+fn release_cursor(mut window: Query<&mut Window, With<PrimaryWindow>>) {
+    let mut window = window.single_mut();
+    window.cursor.visible = true;
+    window.cursor.grab_mode = CursorGrabMode::None;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{config::insert_test_config, gamepad::ActiveGamepad};
+
+    use super::*;
+
+    #[test]
+    fn should_stop_paddle_flush_against_walls() {
+        assert_eq!(clamp_between_walls(500.0, 75.0, -400.0, 400.0), 325.0);
+        assert_eq!(clamp_between_walls(-500.0, 75.0, -400.0, 400.0), -325.0);
+        assert_eq!(clamp_between_walls(10.0, 75.0, -400.0, 400.0), 10.0);
+    }
+
+    #[test]
+    fn should_center_paddle_wider_than_playfield() {
+        assert_eq!(clamp_between_walls(100.0, 500.0, -400.0, 300.0), -50.0);
+    }
+
+    #[test]
+    fn should_keep_paddle_inside_bumpers_when_it_grows() {
+        let mut app = App::new();
+        app.add_systems(Update, clamp_paddle);
+        app.world.spawn((
+            Transform::default(),
+            Dimensions(Vec2::new(800.0, 600.0)),
+            BoundingBox,
+        ));
+        for x in [-395.0, 395.0] {
+            app.world.spawn((
+                Transform::from_xyz(x, -300.0, 0.0),
+                Dimensions(Vec2::new(10.0, 70.0)),
+                Bumper,
+            ));
+        }
+        let paddle = app
+            .world
+            .spawn((
+                Transform::from_xyz(2000.0, -280.0, 0.0),
+                Dimensions(Vec2::new(150.0, 15.0)),
+                Paddle {
+                    max_deflection_angle: 1.0,
+                    english: 0.0,
+                },
+            ))
+            .id();
+
+        app.update();
+        assert_eq!(
+            app.world.get::<Transform>(paddle).unwrap().translation.x,
+            390.0 - 75.0
+        );
+
+        app.world.get_mut::<Dimensions>(paddle).unwrap().0.x = 300.0;
+        app.update();
+        assert_eq!(
+            app.world.get::<Transform>(paddle).unwrap().translation.x,
+            390.0 - 150.0
+        );
+    }
+
+    fn gamepad_app() -> (App, Entity, Gamepad) {
+        let gamepad = Gamepad::new(0);
+        let mut app = App::new();
+        insert_test_config(&mut app);
+        app.insert_resource(FixedTime::new_from_secs(1.0))
+            .insert_resource(Input::<Action>::default())
+            .insert_resource(Axis::<GamepadAxis>::default())
+            .insert_resource(ActiveGamepad(gamepad))
+            .insert_resource(MousePosition::default())
+            .insert_resource(ControlScheme::Keyboard)
+            .add_state::<PlayState>()
+            .add_systems(Update, move_paddle);
+        let paddle = app
+            .world
+            .spawn((
+                Transform::default(),
+                Speed(Vec2::ZERO),
+                PaddleMotion {
+                    max_speed: 200.0,
+                    acceleration: 1000.0,
+                    friction: 1000.0,
+                },
+                Paddle {
+                    max_deflection_angle: 1.0,
+                    english: 0.0,
+                },
+            ))
+            .id();
+        (app, paddle, gamepad)
+    }
+
+    #[test]
+    fn should_scale_paddle_speed_with_stick_deflection() {
+        let (mut app, paddle, gamepad) = gamepad_app();
+        let stick = GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX);
+        let deadzone = 0.15;
+
+        app.world
+            .resource_mut::<Axis<GamepadAxis>>()
+            .set(stick, deadzone + (1.0 - deadzone) / 2.0);
+        app.update();
+        let half = app.world.get::<Transform>(paddle).unwrap().translation.x;
+        assert!((half - 100.0).abs() < 1e-3, "{half}");
+
+        app.world
+            .resource_mut::<Axis<GamepadAxis>>()
+            .set(stick, deadzone / 2.0);
+        app.update();
+        assert_eq!(
+            app.world.get::<Transform>(paddle).unwrap().translation.x,
+            half
+        );
+    }
+
+    #[test]
+    fn should_move_paddle_at_full_speed_on_move_action() {
+        let (mut app, paddle, gamepad) = gamepad_app();
+        app.world
+            .resource_mut::<Axis<GamepadAxis>>()
+            .set(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX), -0.5);
+        app.world
+            .resource_mut::<Input<Action>>()
+            .press(Action::MoveLeft);
+
+        app.update();
+
+        assert_eq!(
+            app.world.get::<Transform>(paddle).unwrap().translation.x,
+            -200.0
+        );
+    }
+
+    #[test]
+    fn should_only_follow_cursor_while_ball_is_in_game() {
+        let (mut app, paddle, _) = gamepad_app();
+        app.insert_resource(ControlScheme::Mouse { max_speed: None })
+            .insert_resource(MousePosition {
+                world: Vec2::new(120.0, 0.0),
+                viewport: Vec2::ZERO,
+            });
+
+        app.update();
+        assert_eq!(
+            app.world.get::<Transform>(paddle).unwrap().translation.x,
+            0.0
+        );
+
+        app.insert_resource(State::new(PlayState::BallInGame));
+        app.update();
+        assert_eq!(
+            app.world.get::<Transform>(paddle).unwrap().translation.x,
+            120.0
+        );
+    }
+
+    const MOTION: PaddleMotion = PaddleMotion {
+        max_speed: 400.0,
+        acceleration: 2000.0,
+        friction: 1000.0,
+    };
+
+    #[test]
+    fn should_speed_paddle_up_to_max_speed() {
+        let mut velocity = 0.0;
+        let mut speeds = Vec::new();
+        for _ in 0..4 {
+            velocity = accelerate(velocity, 1.0, &MOTION, 0.1);
+            speeds.push(velocity);
+        }
+
+        assert_eq!(speeds, vec![200.0, 400.0, 400.0, 400.0]);
+    }
+
+    #[test]
+    fn should_slow_paddle_down_with_friction_once_let_go() {
+        assert_eq!(accelerate(400.0, 0.0, &MOTION, 0.1), 300.0);
+        assert_eq!(accelerate(-50.0, 0.0, &MOTION, 0.1), 0.0);
+    }
+
+    #[test]
+    fn should_turn_paddle_around_at_its_acceleration() {
+        assert_eq!(accelerate(400.0, -1.0, &MOTION, 0.1), 200.0);
+    }
+
+    #[test]
+    fn should_stop_paddle_running_into_a_wall() {
+        let mut app = App::new();
+        app.add_systems(Update, clamp_paddle);
+        app.world.spawn((
+            Transform::default(),
+            Dimensions(Vec2::new(800.0, 600.0)),
+            BoundingBox,
+        ));
+        let paddle = app
+            .world
+            .spawn((
+                Transform::from_xyz(390.0, -280.0, 0.0),
+                Dimensions(Vec2::new(150.0, 15.0)),
+                Speed(Vec2::new(400.0, 0.0)),
+                Paddle {
+                    max_deflection_angle: 1.0,
+                    english: 0.0,
+                },
+            ))
+            .id();
+
+        app.update();
+
+        assert_eq!(app.world.get::<Speed>(paddle).unwrap().0, Vec2::ZERO);
+    }
+
+    #[test]
+    fn should_jump_to_cursor_without_max_speed() {
+        assert_eq!(follow_cursor(0.0, 250.0, None, 1.0 / 60.0), 250.0);
+    }
+
+    #[test]
+    fn should_chase_cursor_at_max_speed() {
+        let delta = 0.25;
 
-    if input.any_pressed([KeyCode::Right, KeyCode::D]) {
-        transform.translation.x += time.delta_seconds() * speed.0.x
+        assert_eq!(follow_cursor(0.0, 250.0, Some(600.0), delta), 150.0);
+        assert_eq!(follow_cursor(0.0, -250.0, Some(600.0), delta), -150.0);
+        assert_eq!(follow_cursor(0.0, 40.0, Some(600.0), delta), 40.0);
     }
 }
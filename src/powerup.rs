@@ -0,0 +1,501 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+    ball::{Ball, SplitBallEvent},
+    block::{BlockDestroyed, BlockSet},
+    catch::CatchConfig,
+    config::{Config, GameConfig},
+    effect::{ApplyEffect, EffectTarget, Stacking, TimedEffectConfig},
+    game::{despawn_with_component, AppState, BoundingBox, SimulationSet},
+    generator::{random_seed, SplitMix64},
+    laser::LaserConfig,
+    level::BlockKind,
+    paddle::{Dimensions, Paddle, Speed},
+    stats::Lives,
+};
+
+/// Drops capsules from destroyed blocks that apply a power-up when the paddle catches them.
+pub struct PowerUpPlugin;
+
+impl Plugin for PowerUpPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PowerUpCollected>()
+            .add_systems(OnExit(AppState::Menu), insert_power_up_rng)
+            .add_systems(
+                FixedUpdate,
+                (
+                    drop_power_ups
+                        .after(BlockSet::Hit)
+                        .before(BlockSet::Despawn),
+                    (
+                        move_capsules,
+                        catch_capsules,
+                        apply_power_ups.in_set(PowerUpSet),
+                    )
+                        .chain(),
+                )
+                    .in_set(SimulationSet::Gameplay)
+                    .distributive_run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(OnExit(AppState::Playing), despawn_with_component::<Capsule>);
+    }
+}
+
+/// Where collected power-ups are applied. Systems handling the events sent for them run after it,
+/// so they take effect in the same tick.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, SystemSet)]
+pub struct PowerUpSet;
+
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PowerUpKind {
+    WidePaddle,
+    SlowBall,
+    MultiBall,
+    ExtraLife,
+    Laser,
+    Catch,
+}
+
+impl PowerUpKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            PowerUpKind::WidePaddle => "Wide paddle",
+            PowerUpKind::SlowBall => "Slow ball",
+            PowerUpKind::MultiBall => "Multi-ball",
+            PowerUpKind::ExtraLife => "Extra life",
+            PowerUpKind::Laser => "Laser",
+            PowerUpKind::Catch => "Catch",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            PowerUpKind::WidePaddle => Color::BLUE,
+            PowerUpKind::SlowBall => Color::ORANGE,
+            PowerUpKind::MultiBall => Color::GREEN,
+            PowerUpKind::ExtraLife => Color::RED,
+            PowerUpKind::Laser => Color::FUCHSIA,
+            PowerUpKind::Catch => Color::CYAN,
+        }
+    }
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub struct PowerUpConfig {
+    /// Speed capsules fall at, in units per second.
+    pub fall_speed: f32,
+    pub capsule_width: f32,
+    pub capsule_height: f32,
+    /// Without one, every game drops different power-ups.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    pub drops: DropTables,
+    /// Multiplies the paddle's width.
+    pub wide_paddle: TimedEffectConfig,
+    /// Multiplies the ball's speed.
+    pub slow_ball: TimedEffectConfig,
+    /// Balls a ball is split into by [`PowerUpKind::MultiBall`].
+    pub multi_ball: u32,
+    pub laser: LaserConfig,
+    pub catch: CatchConfig,
+}
+
+/// What each kind of block may drop when it is destroyed. Indestructible blocks never are.
+#[derive(serde::Deserialize, Debug, Default)]
+pub struct DropTables {
+    #[serde(default)]
+    pub normal: Vec<DropChance>,
+    #[serde(default)]
+    pub explosive: Vec<DropChance>,
+    #[serde(default)]
+    pub invisible: Vec<DropChance>,
+}
+
+impl DropTables {
+    fn for_kind(&self, kind: BlockKind) -> &[DropChance] {
+        match kind {
+            BlockKind::Normal => &self.normal,
+            BlockKind::Explosive { .. } => &self.explosive,
+            BlockKind::Invisible => &self.invisible,
+            BlockKind::Indestructible => &[],
+        }
+    }
+}
+
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct DropChance {
+    pub power_up: PowerUpKind,
+    /// Between 0 and 1. The chances of a table should add up to at most 1.
+    pub chance: f32,
+}
+
+/// Decides which power-ups drop, so a seeded game always drops the same ones.
+#[derive(Resource, Debug)]
+pub struct PowerUpRng(SplitMix64);
+
+/// Falling power-up that takes effect when it touches the paddle.
+#[derive(Component, Debug)]
+pub struct Capsule {
+    pub kind: PowerUpKind,
+    pub size: Vec2,
+}
+
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PowerUpCollected {
+    pub kind: PowerUpKind,
+}
+
+fn insert_power_up_rng(
+    mut commands: Commands,
+    game_config: Res<GameConfig>,
+    assets: Res<Assets<Config>>,
+) {
+    let Some(config) = assets.get(&game_config.config) else {
+        panic!("game config could not be loaded")
+    };
+
+    let seed = config.power_ups.seed.unwrap_or_else(random_seed);
+    commands.insert_resource(PowerUpRng(SplitMix64::new(seed)));
+}
+
+/// Picks the power-up, if any, a block with `drops` drops.
+fn roll(drops: &[DropChance], rng: &mut SplitMix64) -> Option<PowerUpKind> {
+    let roll = rng.next_f32();
+    let mut chance = 0.0;
+    drops.iter().find_map(|drop| {
+        chance += drop.chance;
+        (roll < chance).then_some(drop.power_up)
+    })
+}
+
+fn drop_power_ups(
+    mut commands: Commands,
+    mut reader: EventReader<BlockDestroyed>,
+    mut rng: ResMut<PowerUpRng>,
+    game_config: Res<GameConfig>,
+    assets: Res<Assets<Config>>,
+) {
+    let Some(config) = assets.get(&game_config.config) else {
+        panic!("game config could not be loaded")
+    };
+    let config = &config.power_ups;
+
+    for event in reader.iter() {
+        let Some(kind) = roll(config.drops.for_kind(event.kind), &mut rng.0) else {
+            continue;
+        };
+
+        let size = Vec2::new(config.capsule_width, config.capsule_height);
+        commands.spawn((
+            Capsule { kind, size },
+            Speed(Vec2::NEG_Y * config.fall_speed),
+            SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(size),
+                    color: kind.color(),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(event.position.extend(0.5)),
+                ..Default::default()
+            },
+            Name::from(format!("{kind:?} capsule")),
+        ));
+    }
+}
+
+fn move_capsules(
+    mut capsules: Query<(&mut Transform, &Speed), With<Capsule>>,
+    time: Res<FixedTime>,
+) {
+    let delta = time.period.as_secs_f32();
+    for (mut transform, speed) in &mut capsules {
+        transform.translation += speed.0.extend(0.0) * delta;
+    }
+}
+
+/// Collects the capsules touching the paddle and removes those that fell out of the playfield.
+fn catch_capsules(
+    mut commands: Commands,
+    capsules: Query<(Entity, &Transform, &Capsule)>,
+    paddle: Query<(&GlobalTransform, &Dimensions), With<Paddle>>,
+    bounding_box: Query<(&GlobalTransform, &Dimensions), With<BoundingBox>>,
+    mut writer: EventWriter<PowerUpCollected>,
+) {
+    let paddle = paddle.get_single().ok().map(|(transform, dimensions)| {
+        Rect::from_center_size(transform.translation().truncate(), dimensions.0)
+    });
+    let bottom = bounding_box
+        .get_single()
+        .map_or(f32::NEG_INFINITY, |(transform, dimensions)| {
+            transform.translation().y - dimensions.0.y / 2.0
+        });
+
+    for (entity, transform, capsule) in &capsules {
+        let rect = Rect::from_center_size(transform.translation.truncate(), capsule.size);
+        if paddle.is_some_and(|paddle| !paddle.intersect(rect).is_empty()) {
+            writer.send(PowerUpCollected { kind: capsule.kind });
+            commands.entity(entity).despawn_recursive();
+        } else if rect.max.y < bottom {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Everything power-ups act on. Effects that wear off are applied through [`ApplyEffect`].
+#[derive(SystemParam)]
+struct Targets<'w, 's> {
+    balls: Query<'w, 's, Entity, With<Ball>>,
+    lives: ResMut<'w, Lives>,
+    split: EventWriter<'w, SplitBallEvent>,
+    effects: EventWriter<'w, ApplyEffect>,
+}
+
+fn apply_power_ups(
+    mut reader: EventReader<PowerUpCollected>,
+    mut targets: Targets,
+    game_config: Res<GameConfig>,
+    assets: Res<Assets<Config>>,
+) {
+    let Some(config) = assets.get(&game_config.config) else {
+        panic!("game config could not be loaded")
+    };
+    let config = &config.power_ups;
+
+    for event in reader.iter() {
+        match event.kind {
+            PowerUpKind::WidePaddle => targets.effects.send(ApplyEffect {
+                kind: event.kind,
+                target: Some(EffectTarget::PaddleWidth),
+                config: config.wide_paddle,
+            }),
+            PowerUpKind::SlowBall => targets.effects.send(ApplyEffect {
+                kind: event.kind,
+                target: Some(EffectTarget::BallSpeed),
+                config: config.slow_ball,
+            }),
+            PowerUpKind::MultiBall => {
+                if let Some(ball) = targets.balls.iter().next() {
+                    targets.split.send(SplitBallEvent {
+                        ball,
+                        count: config.multi_ball,
+                    });
+                }
+            }
+            PowerUpKind::ExtraLife => targets.lives.0 += 1,
+            PowerUpKind::Laser => targets.effects.send(ApplyEffect {
+                kind: event.kind,
+                target: None,
+                config: TimedEffectConfig {
+                    factor: 1.0,
+                    duration: config.laser.duration,
+                    stacking: Stacking::Extend,
+                },
+            }),
+            PowerUpKind::Catch => targets.effects.send(ApplyEffect {
+                kind: event.kind,
+                target: None,
+                config: TimedEffectConfig {
+                    factor: 1.0,
+                    duration: config.catch.duration,
+                    stacking: Stacking::Extend,
+                },
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{block::DestroyedBy, config::insert_test_config};
+
+    use super::*;
+
+    fn power_up_app() -> App {
+        let mut app = App::new();
+        insert_test_config(&mut app);
+        app.add_event::<BlockDestroyed>()
+            .add_event::<PowerUpCollected>()
+            .add_event::<SplitBallEvent>()
+            .add_event::<ApplyEffect>()
+            .insert_resource(FixedTime::new_from_secs(0.5))
+            .insert_resource(PowerUpRng(SplitMix64::new(1)))
+            .insert_resource(Lives(3))
+            .add_systems(
+                Update,
+                (move_capsules, catch_capsules, apply_power_ups).chain(),
+            );
+        app.world.spawn((
+            GlobalTransform::default(),
+            Dimensions(Vec2::new(800.0, 600.0)),
+            BoundingBox,
+        ));
+        app.world.spawn((
+            GlobalTransform::from_xyz(0.0, -280.0, 0.0),
+            Dimensions(Vec2::new(150.0, 15.0)),
+            Paddle {
+                max_deflection_angle: 1.0,
+                english: 0.0,
+            },
+        ));
+        app
+    }
+
+    fn spawn_capsule(app: &mut App, kind: PowerUpKind, position: Vec2) -> Entity {
+        app.world
+            .spawn((
+                Capsule {
+                    kind,
+                    size: Vec2::new(30.0, 10.0),
+                },
+                Speed(Vec2::new(0.0, -100.0)),
+                Transform::from_translation(position.extend(0.0)),
+            ))
+            .id()
+    }
+
+    #[test]
+    fn should_roll_drops_by_their_chances() {
+        let mut rng = SplitMix64::new(3);
+        let always = [DropChance {
+            power_up: PowerUpKind::ExtraLife,
+            chance: 1.0,
+        }];
+        let never = [DropChance {
+            power_up: PowerUpKind::ExtraLife,
+            chance: 0.0,
+        }];
+
+        for _ in 0..100 {
+            assert_eq!(roll(&always, &mut rng), Some(PowerUpKind::ExtraLife));
+            assert_eq!(roll(&never, &mut rng), None);
+            assert_eq!(roll(&[], &mut rng), None);
+        }
+    }
+
+    #[test]
+    fn should_roll_same_drops_for_same_seed() {
+        let drops = [
+            DropChance {
+                power_up: PowerUpKind::WidePaddle,
+                chance: 0.3,
+            },
+            DropChance {
+                power_up: PowerUpKind::MultiBall,
+                chance: 0.3,
+            },
+        ];
+        let rolls = |seed| {
+            let mut rng = SplitMix64::new(seed);
+            (0..50).map(|_| roll(&drops, &mut rng)).collect::<Vec<_>>()
+        };
+
+        assert_eq!(rolls(9), rolls(9));
+        assert!(rolls(9).contains(&None));
+        assert!(rolls(9).contains(&Some(PowerUpKind::MultiBall)));
+    }
+
+    #[test]
+    fn should_drop_capsule_where_block_was_destroyed() {
+        let mut app = power_up_app();
+        app.add_systems(Update, drop_power_ups);
+        app.world
+            .resource_mut::<Assets<Config>>()
+            .iter_mut()
+            .for_each(|(_, config)| {
+                config.power_ups.drops.normal = vec![DropChance {
+                    power_up: PowerUpKind::SlowBall,
+                    chance: 1.0,
+                }]
+            });
+
+        app.world
+            .resource_mut::<Events<BlockDestroyed>>()
+            .send(BlockDestroyed {
+                block: Entity::PLACEHOLDER,
+                kind: BlockKind::Normal,
+                position: Vec2::new(40.0, 100.0),
+                destroyed_by: DestroyedBy::Ball(Entity::PLACEHOLDER),
+            });
+        app.update();
+
+        let (capsule, transform) = app
+            .world
+            .query::<(&Capsule, &Transform)>()
+            .single(&app.world);
+        assert_eq!(capsule.kind, PowerUpKind::SlowBall);
+        assert_eq!(transform.translation.truncate(), Vec2::new(40.0, 100.0));
+    }
+
+    #[test]
+    fn should_collect_capsule_caught_by_paddle() {
+        let mut app = power_up_app();
+        let caught = spawn_capsule(&mut app, PowerUpKind::ExtraLife, Vec2::new(60.0, -230.0));
+        let missed = spawn_capsule(&mut app, PowerUpKind::ExtraLife, Vec2::new(200.0, -230.0));
+
+        app.update();
+        assert!(app.world.get_entity(caught).is_none());
+        assert!(app.world.get_entity(missed).is_some());
+        assert_eq!(app.world.resource::<Lives>().0, 4);
+    }
+
+    #[test]
+    fn should_remove_capsule_that_fell_out_of_playfield() {
+        let mut app = power_up_app();
+        let capsule = spawn_capsule(&mut app, PowerUpKind::ExtraLife, Vec2::new(300.0, -300.0));
+
+        app.update();
+
+        assert!(app.world.get_entity(capsule).is_none());
+        assert_eq!(app.world.resource::<Lives>().0, 3);
+    }
+
+    fn collect(app: &mut App, kind: PowerUpKind) {
+        app.world
+            .resource_mut::<Events<PowerUpCollected>>()
+            .send(PowerUpCollected { kind });
+        app.update();
+    }
+
+    #[test]
+    fn should_apply_timed_effects_for_wide_paddle_and_slow_ball() {
+        let mut app = power_up_app();
+
+        collect(&mut app, PowerUpKind::WidePaddle);
+        collect(&mut app, PowerUpKind::SlowBall);
+
+        let events = app.world.resource::<Events<ApplyEffect>>();
+        let mut reader = events.get_reader();
+        let applied: Vec<_> = reader
+            .iter(events)
+            .map(|event| (event.kind, event.target, event.config.factor))
+            .collect();
+        assert_eq!(
+            applied,
+            vec![
+                (
+                    PowerUpKind::WidePaddle,
+                    Some(EffectTarget::PaddleWidth),
+                    1.5
+                ),
+                (PowerUpKind::SlowBall, Some(EffectTarget::BallSpeed), 0.7)
+            ]
+        );
+    }
+
+    #[test]
+    fn should_split_ball_for_multi_ball() {
+        let mut app = power_up_app();
+        let ball = app
+            .world
+            .spawn((Ball { radius: 5.0 }, Speed(Vec2::new(0.0, 300.0))))
+            .id();
+
+        collect(&mut app, PowerUpKind::MultiBall);
+
+        let events = app.world.resource::<Events<SplitBallEvent>>();
+        let mut reader = events.get_reader();
+        let split: Vec<_> = reader.iter(events).collect();
+        assert_eq!(split.len(), 1);
+        assert_eq!((split[0].ball, split[0].count), (ball, 3));
+    }
+}
@@ -0,0 +1,278 @@
+use bevy::prelude::*;
+
+use crate::{
+    ball::BallCollisionEvent,
+    block::{block_bundle, Block, Blocks, GridPosition},
+    game::{AppState, PlayState, SimulationSet},
+    generator::{generate, GeneratorConfig, SplitMix64},
+    layout::GridLayout,
+    paddle::Paddle,
+};
+
+/// Moves the blocks down a row at a time while new rows appear at the top.
+pub struct ProgressivePlugin;
+
+impl Plugin for ProgressivePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            descend_blocks
+                .in_set(SimulationSet::Gameplay)
+                .run_if(resource_exists::<Progression>())
+                .run_if(in_state(AppState::Playing).and_then(in_state(PlayState::BallInGame))),
+        );
+    }
+}
+
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ProgressiveConfig {
+    /// Rows of blocks the level starts with.
+    pub rows: u32,
+    pub descend: Descend,
+    /// Pattern of the first rows and of every row added later on.
+    pub generator: GeneratorConfig,
+}
+
+#[derive(serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Descend {
+    Every { seconds: f32 },
+    PaddleHits { hits: u32 },
+}
+
+/// State of a progressive level being played.
+#[derive(Resource, Debug)]
+pub struct Progression {
+    config: ProgressiveConfig,
+    layout: GridLayout,
+    rng: SplitMix64,
+    elapsed: f32,
+    paddle_hits: u32,
+    /// Rows the blocks have moved down since the level started.
+    descended: u32,
+}
+
+impl Progression {
+    pub fn new(config: ProgressiveConfig, seed: u64, layout: GridLayout) -> Self {
+        Self {
+            config,
+            layout,
+            rng: SplitMix64::new(seed),
+            elapsed: 0.0,
+            paddle_hits: 0,
+            descended: 0,
+        }
+    }
+
+    /// Advances the progression by `delta` seconds and `paddle_hits` hits, returning whether the
+    /// blocks have to move down a row.
+    fn advance(&mut self, delta: f32, paddle_hits: u32) -> bool {
+        let due = match self.config.descend {
+            Descend::Every { seconds } => {
+                self.elapsed += delta;
+                let due = self.elapsed >= seconds;
+                if due {
+                    self.elapsed -= seconds;
+                }
+                due
+            }
+            Descend::PaddleHits { hits } => {
+                self.paddle_hits += paddle_hits;
+                let due = self.paddle_hits >= hits;
+                if due {
+                    self.paddle_hits -= hits;
+                }
+                due
+            }
+        };
+
+        if due {
+            self.descended += 1;
+        }
+        due
+    }
+}
+
+fn descend_blocks(
+    mut commands: Commands,
+    mut progression: ResMut<Progression>,
+    mut reader: EventReader<BallCollisionEvent>,
+    time: Res<FixedTime>,
+    paddles: Query<With<Paddle>>,
+    mut parent: Query<(Entity, &mut Transform), With<Blocks>>,
+    mut blocks: Query<&mut GridPosition, With<Block>>,
+) {
+    let paddle_hits = reader
+        .iter()
+        .filter(|event| paddles.contains(event.with))
+        .count() as u32;
+    if !progression.advance(time.period.as_secs_f32(), paddle_hits) {
+        return;
+    }
+
+    let Ok((parent, mut transform)) = parent.get_single_mut() else {
+        return;
+    };
+    let layout = progression.layout;
+    transform.translation.y -= layout.row_step();
+    for mut position in &mut blocks {
+        position.row += 1;
+    }
+
+    let generator = progression.config.generator;
+    let seed = progression.rng.next_u64();
+    let offset = Vec2::Y * layout.row_step() * progression.descended as f32;
+    commands.entity(parent).with_children(|builder| {
+        for block in generate(
+            generator.pattern,
+            generator.density,
+            seed,
+            layout.columns,
+            1,
+        ) {
+            let cell = layout.cell(block.column, 0);
+            let cell = Rect::from_center_size(cell.center() + offset, cell.size());
+            builder.spawn(block_bundle(block, cell));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{config::BlockConfig, generator::Pattern, level::BlockKind, paddle::Dimensions};
+
+    use super::*;
+
+    fn progressive_app(descend: Descend) -> (App, Entity, Entity) {
+        let mut app = App::new();
+        app.add_event::<BallCollisionEvent>()
+            .insert_resource(FixedTime::new_from_secs(1.0))
+            .insert_resource(Progression::new(
+                ProgressiveConfig {
+                    rows: 1,
+                    descend,
+                    generator: GeneratorConfig {
+                        pattern: Pattern::Noise,
+                        density: 1.0,
+                        seed: Some(1),
+                    },
+                },
+                1,
+                GridLayout::new(
+                    &BlockConfig {
+                        width: 60.0,
+                        height: 15.0,
+                        horizontal_offset: 10.0,
+                        vertical_offset: 5.0,
+                        rows: 10,
+                        columns: 4,
+                        offset_from_top: 100.0,
+                    },
+                    Vec2::new(800.0, 600.0),
+                )
+                .unwrap(),
+            ))
+            .add_systems(Update, descend_blocks);
+
+        let parent = app.world.spawn((Blocks, TransformBundle::default())).id();
+        let block = app
+            .world
+            .spawn((Block, GridPosition { column: 1, row: 0 }))
+            .set_parent(parent)
+            .id();
+
+        (app, parent, block)
+    }
+
+    fn new_rows(app: &mut App, existing: Entity) -> Vec<(GridPosition, Vec3)> {
+        app.world
+            .query_filtered::<(Entity, &GridPosition, &Transform), With<Block>>()
+            .iter(&app.world)
+            .filter(|(entity, ..)| *entity != existing)
+            .map(|(_, position, transform)| (*position, transform.translation))
+            .collect()
+    }
+
+    #[test]
+    fn should_move_blocks_down_a_row_every_few_seconds() {
+        let (mut app, parent, block) = progressive_app(Descend::Every { seconds: 2.0 });
+
+        app.update();
+        assert_eq!(
+            app.world.get::<Transform>(parent).unwrap().translation.y,
+            0.0
+        );
+
+        app.update();
+        assert_eq!(
+            app.world.get::<Transform>(parent).unwrap().translation.y,
+            -20.0
+        );
+        assert_eq!(
+            *app.world.get::<GridPosition>(block).unwrap(),
+            GridPosition { column: 1, row: 1 }
+        );
+    }
+
+    #[test]
+    fn should_add_new_row_at_the_top() {
+        let (mut app, parent, block) = progressive_app(Descend::Every { seconds: 1.0 });
+
+        app.update();
+
+        let added = new_rows(&mut app, block);
+        assert_eq!(added.len(), 4);
+        let parent_y = app.world.get::<Transform>(parent).unwrap().translation.y;
+        for (position, translation) in added {
+            assert_eq!(position.row, 0);
+            assert_eq!(
+                translation.y + parent_y,
+                300.0 - 100.0 - 7.5,
+                "new rows appear where the first row started"
+            );
+        }
+        assert!(app
+            .world
+            .query::<&BlockKind>()
+            .iter(&app.world)
+            .all(|kind| *kind == BlockKind::Normal));
+    }
+
+    #[test]
+    fn should_move_blocks_down_after_paddle_hits() {
+        let (mut app, parent, _) = progressive_app(Descend::PaddleHits { hits: 2 });
+        let paddle = app
+            .world
+            .spawn((
+                Paddle {
+                    max_deflection_angle: 1.0,
+                    english: 0.0,
+                },
+                Dimensions(Vec2::new(150.0, 15.0)),
+            ))
+            .id();
+        let hit_paddle = |app: &mut App| {
+            app.world
+                .resource_mut::<Events<BallCollisionEvent>>()
+                .send(BallCollisionEvent {
+                    ball: Entity::PLACEHOLDER,
+                    with: paddle,
+                    contact: Vec2::ZERO,
+                    normal: Vec2::Y,
+                    depth: 0.0,
+                });
+            app.update();
+        };
+
+        hit_paddle(&mut app);
+        assert_eq!(
+            app.world.get::<Transform>(parent).unwrap().translation.y,
+            0.0
+        );
+
+        hit_paddle(&mut app);
+        assert_eq!(
+            app.world.get::<Transform>(parent).unwrap().translation.y,
+            -20.0
+        );
+    }
+}
@@ -0,0 +1,370 @@
+use bevy::prelude::*;
+
+use crate::{
+    ball::BallCollisionEvent,
+    block::{BlockDestroyed, BlockHit, BlockSet, GridPosition},
+    config::{Config, GameConfig},
+    game::{AppState, PlayState, SimulationSet},
+    level::BlockKind,
+    paddle::Paddle,
+    stats::Lives,
+};
+
+/// Points of the current game, kept from one level to the next.
+#[derive(Resource, Debug, Default, PartialEq, Eq)]
+pub struct Score {
+    pub points: u32,
+    /// Breakable blocks hit since the ball last touched the paddle.
+    pub combo: u32,
+}
+
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Score>()
+            .add_systems(OnExit(AppState::Menu), reset_score)
+            .add_systems(
+                FixedUpdate,
+                (break_combo, score_blocks)
+                    .chain()
+                    .in_set(SimulationSet::Gameplay)
+                    .after(BlockSet::Hit)
+                    .before(BlockSet::Despawn)
+                    .run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(OnEnter(AppState::Playing), end_combo)
+            .add_systems(OnExit(PlayState::BallInGame), end_combo)
+            .add_systems(OnEnter(AppState::LevelComplete), award_level_bonus)
+            .add_systems(OnEnter(AppState::Victory), award_level_bonus);
+    }
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub struct ScoreConfig {
+    pub block_points: BlockPoints,
+    /// Extra points for every row a block is above the bottom row of the grid.
+    pub row_points: u32,
+    /// Added to the multiplier for every block hit in a row after the first.
+    pub combo_step: f32,
+    pub max_multiplier: f32,
+    /// Awarded for every cleared level.
+    pub level_clear_bonus: u32,
+    /// Awarded for every life left when a level is cleared.
+    pub life_bonus: u32,
+}
+
+/// Base points for destroying each kind of block. Indestructible blocks never are.
+#[derive(serde::Deserialize, Debug)]
+pub struct BlockPoints {
+    pub normal: u32,
+    pub explosive: u32,
+    pub invisible: u32,
+}
+
+impl BlockPoints {
+    fn for_kind(&self, kind: BlockKind) -> u32 {
+        match kind {
+            BlockKind::Normal => self.normal,
+            BlockKind::Explosive { .. } => self.explosive,
+            BlockKind::Invisible => self.invisible,
+            BlockKind::Indestructible => 0,
+        }
+    }
+}
+
+impl ScoreConfig {
+    /// Multiplier for a block destroyed with `combo` blocks hit since the paddle.
+    fn multiplier(&self, combo: u32) -> f32 {
+        let hits_in_a_row = combo.saturating_sub(1) as f32;
+        (1.0 + self.combo_step * hits_in_a_row).min(self.max_multiplier)
+    }
+}
+
+fn reset_score(mut score: ResMut<Score>) {
+    *score = Score::default();
+}
+
+fn end_combo(mut score: ResMut<Score>) {
+    score.combo = 0;
+}
+
+/// Starts counting hits over once a ball touches the paddle.
+fn break_combo(
+    mut reader: EventReader<BallCollisionEvent>,
+    paddle: Query<With<Paddle>>,
+    mut score: ResMut<Score>,
+) {
+    if reader.iter().any(|event| paddle.contains(event.with)) {
+        score.combo = 0;
+    }
+}
+
+fn score_blocks(
+    mut hits: EventReader<BlockHit>,
+    mut destroyed: EventReader<BlockDestroyed>,
+    positions: Query<&GridPosition>,
+    kinds: Query<&BlockKind>,
+    mut score: ResMut<Score>,
+    game_config: Res<GameConfig>,
+    assets: Res<Assets<Config>>,
+) {
+    let Some(config) = assets.get(&game_config.config) else {
+        panic!("game config could not be loaded")
+    };
+
+    score.combo += hits
+        .iter()
+        .filter(|hit| kinds.get(hit.block).is_ok_and(BlockKind::is_breakable))
+        .count() as u32;
+
+    let multiplier = config.score.multiplier(score.combo);
+    for event in destroyed.iter() {
+        let rows_above_bottom = positions.get(event.block).map_or(0, |position| {
+            config.block.rows.saturating_sub(position.row + 1)
+        });
+        let points = config.score.block_points.for_kind(event.kind)
+            + config.score.row_points * rows_above_bottom;
+        score.points += (points as f32 * multiplier).round() as u32;
+    }
+}
+
+fn award_level_bonus(
+    mut score: ResMut<Score>,
+    lives: Res<Lives>,
+    game_config: Res<GameConfig>,
+    assets: Res<Assets<Config>>,
+) {
+    let Some(config) = assets.get(&game_config.config) else {
+        panic!("game config could not be loaded")
+    };
+
+    score.points += config.score.level_clear_bonus + config.score.life_bonus * lives.0;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{block::DestroyedBy, config::insert_test_config};
+
+    use super::*;
+
+    fn score_app() -> (App, Entity) {
+        let mut app = App::new();
+        insert_test_config(&mut app);
+        app.add_event::<BallCollisionEvent>()
+            .add_event::<BlockHit>()
+            .add_event::<BlockDestroyed>()
+            .init_resource::<Score>()
+            .add_systems(Update, (break_combo, score_blocks).chain());
+        app.world
+            .resource_mut::<Assets<Config>>()
+            .iter_mut()
+            .for_each(|(_, config)| {
+                config.block.rows = 10;
+                config.score = ScoreConfig {
+                    block_points: BlockPoints {
+                        normal: 10,
+                        explosive: 20,
+                        invisible: 40,
+                    },
+                    row_points: 1,
+                    combo_step: 0.5,
+                    max_multiplier: 2.0,
+                    level_clear_bonus: 1000,
+                    life_bonus: 100,
+                }
+            });
+        let paddle = app
+            .world
+            .spawn(Paddle {
+                max_deflection_angle: 1.0,
+                english: 0.0,
+            })
+            .id();
+        (app, paddle)
+    }
+
+    /// Sends the events of a ball hitting and destroying a block of `kind` in the bottom row.
+    fn destroy(app: &mut App, kind: BlockKind) {
+        let block = app
+            .world
+            .spawn((GridPosition { column: 0, row: 9 }, kind))
+            .id();
+        let by = DestroyedBy::Ball(Entity::PLACEHOLDER);
+        app.world.resource_mut::<Events<BlockHit>>().send(BlockHit {
+            block,
+            by,
+            remaining_hp: 0,
+        });
+        app.world
+            .resource_mut::<Events<BlockDestroyed>>()
+            .send(BlockDestroyed {
+                block,
+                kind,
+                position: Vec2::ZERO,
+                destroyed_by: by,
+            });
+        app.update();
+    }
+
+    fn touch_paddle(app: &mut App, paddle: Entity) {
+        app.world
+            .resource_mut::<Events<BallCollisionEvent>>()
+            .send(BallCollisionEvent {
+                ball: Entity::PLACEHOLDER,
+                with: paddle,
+                contact: Vec2::ZERO,
+                normal: Vec2::Y,
+                depth: 0.0,
+            });
+        app.update();
+    }
+
+    #[test]
+    fn should_score_points_of_block_kind_and_row() {
+        let (mut app, paddle) = score_app();
+        let block = app.world.spawn(GridPosition { column: 3, row: 2 }).id();
+
+        destroy(&mut app, BlockKind::Invisible);
+        touch_paddle(&mut app, paddle);
+        app.world
+            .resource_mut::<Events<BlockDestroyed>>()
+            .send(BlockDestroyed {
+                block,
+                kind: BlockKind::Normal,
+                position: Vec2::ZERO,
+                destroyed_by: DestroyedBy::Explosion(Entity::PLACEHOLDER),
+            });
+        app.update();
+
+        assert_eq!(app.world.resource::<Score>().points, 40 + 10 + 7);
+    }
+
+    #[test]
+    fn should_multiply_points_of_blocks_hit_in_a_row() {
+        let (mut app, _) = score_app();
+
+        for _ in 0..4 {
+            destroy(&mut app, BlockKind::Normal);
+        }
+
+        assert_eq!(
+            *app.world.resource::<Score>(),
+            Score {
+                points: 10 + 15 + 20 + 20,
+                combo: 4
+            }
+        );
+    }
+
+    #[test]
+    fn should_break_combo_when_ball_touches_paddle() {
+        let (mut app, paddle) = score_app();
+
+        destroy(&mut app, BlockKind::Normal);
+        destroy(&mut app, BlockKind::Normal);
+        touch_paddle(&mut app, paddle);
+        destroy(&mut app, BlockKind::Normal);
+
+        assert_eq!(
+            *app.world.resource::<Score>(),
+            Score {
+                points: 10 + 15 + 10,
+                combo: 1
+            }
+        );
+    }
+
+    #[test]
+    fn should_keep_combo_when_ball_bounces_off_anything_else() {
+        let (mut app, _) = score_app();
+        let wall = app.world.spawn_empty().id();
+
+        destroy(&mut app, BlockKind::Normal);
+        touch_paddle(&mut app, wall);
+        destroy(&mut app, BlockKind::Explosive { radius: 1 });
+
+        assert_eq!(app.world.resource::<Score>().points, 10 + 30);
+    }
+
+    #[test]
+    fn should_not_count_hits_on_indestructible_blocks_towards_combo() {
+        let (mut app, _) = score_app();
+        let wall = app
+            .world
+            .spawn((
+                GridPosition { column: 0, row: 0 },
+                BlockKind::Indestructible,
+            ))
+            .id();
+
+        destroy(&mut app, BlockKind::Normal);
+        for _ in 0..3 {
+            app.world.resource_mut::<Events<BlockHit>>().send(BlockHit {
+                block: wall,
+                by: DestroyedBy::Ball(Entity::PLACEHOLDER),
+                remaining_hp: 1,
+            });
+            app.update();
+        }
+        destroy(&mut app, BlockKind::Normal);
+
+        assert_eq!(
+            *app.world.resource::<Score>(),
+            Score {
+                points: 10 + 15,
+                combo: 2
+            }
+        );
+    }
+
+    #[test]
+    fn should_award_bonus_for_cleared_level_and_lives_left() {
+        let (mut app, _) = score_app();
+        app.insert_resource(Lives(2))
+            .insert_resource(Score {
+                points: 50,
+                combo: 3,
+            })
+            .add_systems(Update, award_level_bonus);
+
+        app.update();
+
+        assert_eq!(app.world.resource::<Score>().points, 50 + 1000 + 200);
+    }
+
+    fn go_to(app: &mut App, state: AppState) {
+        app.world.resource_mut::<NextState<AppState>>().set(state);
+        app.update();
+    }
+
+    #[test]
+    fn should_keep_score_between_levels_and_reset_it_for_new_game() {
+        let mut app = App::new();
+        insert_test_config(&mut app);
+        app.add_plugins(ScorePlugin)
+            .add_state::<AppState>()
+            .add_state::<PlayState>()
+            .insert_resource(Lives(2))
+            .add_systems(Update, apply_state_transition::<AppState>);
+
+        go_to(&mut app, AppState::Menu);
+        go_to(&mut app, AppState::Playing);
+        app.world.resource_mut::<Score>().points = 30;
+        go_to(&mut app, AppState::LevelComplete);
+        go_to(&mut app, AppState::Playing);
+        let bonus = app
+            .world
+            .resource::<Assets<Config>>()
+            .iter()
+            .map(|(_, config)| config.score.level_clear_bonus + config.score.life_bonus * 2)
+            .next()
+            .unwrap();
+        assert_eq!(app.world.resource::<Score>().points, 30 + bonus);
+
+        go_to(&mut app, AppState::GameOver);
+        go_to(&mut app, AppState::Menu);
+        go_to(&mut app, AppState::Playing);
+        assert_eq!(*app.world.resource::<Score>(), Score::default());
+    }
+}
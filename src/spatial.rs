@@ -0,0 +1,204 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{collision::Aabb, game::BoundingBox, paddle::Dimensions};
+
+/// Uniform grid that buckets colliders by the cells their rectangle covers, so collision queries
+/// only have to look at what is nearby instead of at every collider in the world.
+#[derive(Resource, Debug)]
+pub struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<IVec2, Vec<Entity>>,
+    entries: HashMap<Entity, Aabb>,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::default(),
+            entries: HashMap::default(),
+        }
+    }
+
+    /// Adds `entity` to the grid, replacing where it was before if it was already in it.
+    pub fn insert(&mut self, entity: Entity, aabb: Aabb) {
+        self.remove(entity);
+
+        for cell in self.cells_covering(aabb) {
+            self.cells.entry(cell).or_default().push(entity);
+        }
+        self.entries.insert(entity, aabb);
+    }
+
+    pub fn remove(&mut self, entity: Entity) {
+        let Some(aabb) = self.entries.remove(&entity) else {
+            return;
+        };
+
+        for cell in self.cells_covering(aabb) {
+            if let Some(entities) = self.cells.get_mut(&cell) {
+                entities.retain(|other| *other != entity);
+                if entities.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns every entity whose rectangle shares a cell with `area`, ordered by entity so the
+    /// result does not depend on how the grid is laid out in memory.
+    pub fn query(&self, area: Aabb) -> Vec<(Entity, Aabb)> {
+        let mut found: Vec<_> = self
+            .cells_covering(area)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .map(|entity| (*entity, self.entries[entity]))
+            .collect();
+
+        found.sort_unstable_by_key(|(entity, _)| *entity);
+        found.dedup_by_key(|(entity, _)| *entity);
+        found
+    }
+
+    fn cells_covering(&self, aabb: Aabb) -> impl Iterator<Item = IVec2> {
+        let min = ((aabb.center - aabb.half_extents) / self.cell_size)
+            .floor()
+            .as_ivec2();
+        let max = ((aabb.center + aabb.half_extents) / self.cell_size)
+            .floor()
+            .as_ivec2();
+
+        (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+    }
+}
+
+type ChangedCollider = (
+    Or<(Changed<GlobalTransform>, Changed<Dimensions>)>,
+    Without<BoundingBox>,
+);
+
+/// Keeps the grid in sync with every collider except the bounding box, which surrounds everything
+/// and is always checked on its own.
+pub fn update_spatial_grid(
+    mut grid: ResMut<SpatialGrid>,
+    moved: Query<(Entity, &GlobalTransform, &Dimensions), ChangedCollider>,
+) {
+    for (entity, transform, dimensions) in &moved {
+        grid.insert(
+            entity,
+            Aabb::new(transform.translation().truncate(), dimensions.0),
+        );
+    }
+}
+
+/// Drops colliders that were despawned or lost their [`Dimensions`] from the grid.
+///
+/// Removals are only reported for the frame they happened in, so this has to run every frame
+/// instead of on simulation ticks, which some frames have none of. Until then, the colliders left
+/// behind are skipped because they can't be looked up anymore.
+pub fn forget_removed_colliders(
+    mut grid: ResMut<SpatialGrid>,
+    mut removed: RemovedComponents<Dimensions>,
+    colliders: Query<(), With<Dimensions>>,
+) {
+    for entity in removed.iter() {
+        if !colliders.contains(entity) {
+            grid.remove(entity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entities(found: &[(Entity, Aabb)]) -> Vec<u32> {
+        found.iter().map(|(entity, _)| entity.index()).collect()
+    }
+
+    #[test]
+    fn should_only_return_entities_near_queried_area() {
+        let mut grid = SpatialGrid::new(50.0);
+        grid.insert(
+            Entity::from_raw(0),
+            Aabb::new(Vec2::ZERO, Vec2::new(60.0, 15.0)),
+        );
+        grid.insert(
+            Entity::from_raw(1),
+            Aabb::new(Vec2::new(500.0, 0.0), Vec2::new(60.0, 15.0)),
+        );
+
+        let found = grid.query(Aabb::new(Vec2::new(10.0, 10.0), Vec2::splat(10.0)));
+
+        assert_eq!(entities(&found), [0]);
+    }
+
+    #[test]
+    fn should_return_entity_spanning_several_cells_once() {
+        let mut grid = SpatialGrid::new(10.0);
+        grid.insert(
+            Entity::from_raw(7),
+            Aabb::new(Vec2::ZERO, Vec2::new(100.0, 100.0)),
+        );
+
+        let found = grid.query(Aabb::new(Vec2::ZERO, Vec2::new(100.0, 100.0)));
+
+        assert_eq!(entities(&found), [7]);
+    }
+
+    #[test]
+    fn should_follow_moved_entity_and_forget_removed_one() {
+        let mut grid = SpatialGrid::new(50.0);
+        let moving = Entity::from_raw(0);
+        let removed = Entity::from_raw(1);
+        grid.insert(moving, Aabb::new(Vec2::ZERO, Vec2::splat(10.0)));
+        grid.insert(removed, Aabb::new(Vec2::ZERO, Vec2::splat(10.0)));
+
+        grid.insert(moving, Aabb::new(Vec2::new(300.0, 0.0), Vec2::splat(10.0)));
+        grid.remove(removed);
+
+        assert!(grid
+            .query(Aabb::new(Vec2::ZERO, Vec2::splat(10.0)))
+            .is_empty());
+        assert_eq!(
+            entities(&grid.query(Aabb::new(Vec2::new(300.0, 0.0), Vec2::splat(10.0)))),
+            [0]
+        );
+        assert_eq!(grid.len(), 1);
+    }
+
+    #[test]
+    fn should_drop_despawned_colliders_from_grid() {
+        let mut app = App::new();
+        app.insert_resource(SpatialGrid::new(50.0));
+        app.add_systems(Update, (update_spatial_grid, forget_removed_colliders));
+
+        let block = app
+            .world
+            .spawn((
+                GlobalTransform::from_xyz(0.0, 100.0, 0.0),
+                Dimensions(Vec2::new(60.0, 15.0)),
+            ))
+            .id();
+        app.world.spawn((
+            GlobalTransform::default(),
+            Dimensions(Vec2::new(800.0, 600.0)),
+            BoundingBox,
+        ));
+
+        app.update();
+        assert_eq!(app.world.resource::<SpatialGrid>().len(), 1);
+
+        app.world.despawn(block);
+        app.update();
+        assert!(app.world.resource::<SpatialGrid>().is_empty());
+    }
+}
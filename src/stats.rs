@@ -1,9 +1,11 @@
-use bevy::{prelude::*, sprite::collide_aabb::Collision};
+use bevy::prelude::*;
 
 use crate::{
-    ball::BallCollisionEvent,
+    ball::{ball_touched_bottom, LastBallLost},
+    block::Block,
     config::{Config, GameConfig},
-    game::{AppState, BoundingBox},
+    game::{AppState, SimulationSet},
+    paddle::{Dimensions, Paddle},
 };
 
 #[derive(Resource)]
@@ -13,10 +15,13 @@ pub struct StatsPlugin;
 
 impl Plugin for StatsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(AppState::Playing), insert_lifes)
+        app.add_systems(OnExit(AppState::Menu), insert_lifes)
             .add_systems(
-                Update,
+                FixedUpdate,
                 (decrease_lifes_counter, game_over)
+                    .chain()
+                    .in_set(SimulationSet::Gameplay)
+                    .after(ball_touched_bottom)
                     .distributive_run_if(in_state(AppState::Playing)),
             );
     }
@@ -30,48 +35,53 @@ fn insert_lifes(mut commands: Commands, game_config: Res<GameConfig>, assets: Re
     commands.insert_resource(Lives(config.stats.lifes));
 }
 
-fn decrease_lifes_counter(
-    mut lifes: ResMut<Lives>,
-    mut reader: EventReader<BallCollisionEvent>,
-    bounding_box: Query<With<BoundingBox>>,
-) {
-    for event in reader.iter() {
-        if bounding_box.get(event.with).is_ok() && event.collision == Collision::Bottom {
-            lifes.0 -= 1;
-        }
+fn decrease_lifes_counter(mut lifes: ResMut<Lives>, mut reader: EventReader<LastBallLost>) {
+    for _ in reader.iter() {
+        lifes.0 = lifes.0.saturating_sub(1);
     }
 }
 
-fn game_over(mut state: ResMut<NextState<AppState>>, lifes: Res<Lives>) {
-    if lifes.0 == 0 {
+fn game_over(
+    mut state: ResMut<NextState<AppState>>,
+    lifes: Res<Lives>,
+    blocks: Query<(&GlobalTransform, &Dimensions), With<Block>>,
+    paddle: Query<(&GlobalTransform, &Dimensions), With<Paddle>>,
+) {
+    if lifes.0 == 0 || blocks_reached_paddle(&blocks, &paddle) {
         state.set(AppState::GameOver)
     }
 }
 
+/// Whether the lowest block has come down to the top of the paddle.
+fn blocks_reached_paddle(
+    blocks: &Query<(&GlobalTransform, &Dimensions), With<Block>>,
+    paddle: &Query<(&GlobalTransform, &Dimensions), With<Paddle>>,
+) -> bool {
+    let Ok((paddle_transform, paddle_dimensions)) = paddle.get_single() else {
+        return false;
+    };
+    let paddle_top = paddle_transform.translation().y + paddle_dimensions.0.y / 2.0;
+
+    blocks.iter().any(|(transform, dimensions)| {
+        transform.translation().y - dimensions.0.y / 2.0 <= paddle_top
+    })
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::ball::Ball;
-
     use super::*;
 
     #[test]
-    fn should_decrease_lifes_counter_on_bounding_box_bottom_collision() {
+    fn should_decrease_lifes_counter_when_last_ball_is_lost() {
         let mut app = App::new();
         app.insert_resource(Lives(3));
-        app.add_event::<BallCollisionEvent>();
+        app.add_event::<LastBallLost>();
 
         app.add_systems(Update, decrease_lifes_counter);
 
-        let bounding_box = app.world.spawn(BoundingBox).id();
-        let ball = app.world.spawn(Ball { radius: 10.0 }).id();
-
         app.world
-            .resource_mut::<Events<BallCollisionEvent>>()
-            .send(BallCollisionEvent {
-                ball,
-                with: bounding_box,
-                collision: Collision::Bottom,
-            });
+            .resource_mut::<Events<LastBallLost>>()
+            .send(LastBallLost);
 
         app.update();
 
@@ -83,10 +93,7 @@ mod tests {
         let mut app = App::new();
         app.insert_resource(Lives(1));
         app.add_state::<AppState>();
-        app.add_event::<BallCollisionEvent>();
-
-        let bounding_box = app.world.spawn(BoundingBox).id();
-        let ball = app.world.spawn(Ball { radius: 10.0 }).id();
+        app.add_event::<LastBallLost>();
 
         app.add_systems(
             Update,
@@ -99,16 +106,53 @@ mod tests {
         );
 
         app.world
-            .resource_mut::<Events<BallCollisionEvent>>()
-            .send(BallCollisionEvent {
-                ball,
-                with: bounding_box,
-                collision: Collision::Bottom,
-            });
+            .resource_mut::<Events<LastBallLost>>()
+            .send(LastBallLost);
 
         app.update();
 
         let state = app.world.resource::<State<AppState>>().get();
         assert_eq!(*state, AppState::GameOver);
     }
+
+    fn spawn_at(app: &mut App, y: f32, component: impl Component) {
+        app.world.spawn((
+            GlobalTransform::from_xyz(0.0, y, 0.0),
+            Dimensions(Vec2::new(60.0, 15.0)),
+            component,
+        ));
+    }
+
+    #[test]
+    fn should_set_state_to_game_over_when_blocks_reach_paddle() {
+        let mut app = App::new();
+        app.insert_resource(Lives(3));
+        app.add_state::<AppState>();
+        app.add_systems(
+            Update,
+            (game_over, apply_state_transition::<AppState>).chain(),
+        );
+
+        spawn_at(
+            &mut app,
+            -280.0,
+            Paddle {
+                max_deflection_angle: 1.0,
+                english: 0.0,
+            },
+        );
+        spawn_at(&mut app, -200.0, Block);
+        app.update();
+        assert_eq!(
+            *app.world.resource::<State<AppState>>().get(),
+            AppState::AssetLoading
+        );
+
+        spawn_at(&mut app, -270.0, Block);
+        app.update();
+        assert_eq!(
+            *app.world.resource::<State<AppState>>().get(),
+            AppState::GameOver
+        );
+    }
 }
@@ -2,8 +2,13 @@ use bevy::{prelude::*, window::PrimaryWindow};
 
 use crate::{
     ball::Bounces,
+    config::{Config, GameConfig},
     debug::{Drag, DragEvent, MousePosition},
+    effect::ActiveEffect,
     game::{despawn_with_component, AppState},
+    generator::LevelSeed,
+    level::{level_count, CurrentLevel},
+    score::Score,
     stats::Lives,
 };
 
@@ -16,6 +21,17 @@ impl Plugin for UiPlugin {
         app.add_systems(OnEnter(AppState::Menu), spawn_menu)
             .add_systems(OnExit(AppState::Menu), despawn_with_component::<Menu>)
             .add_systems(OnEnter(AppState::Playing), spawn_debug_ui)
+            .add_systems(OnExit(AppState::Playing), despawn_with_component::<Hud>)
+            .add_systems(
+                OnEnter(AppState::LevelComplete),
+                spawn_level_complete_message,
+            )
+            .add_systems(OnEnter(AppState::Victory), spawn_victory_message)
+            .add_systems(
+                OnExit(AppState::LevelComplete),
+                despawn_with_component::<Message>,
+            )
+            .add_systems(OnExit(AppState::Victory), despawn_with_component::<Message>)
             .add_systems(
                 Update,
                 (update_bounce_counter.run_if(resource_changed::<Bounces>()),)
@@ -26,6 +42,9 @@ impl Plugin for UiPlugin {
                 (
                     spawn_measuring_tape,
                     update_lifes_counter.run_if(resource_exists_and_changed::<Lives>()),
+                    update_score_text.run_if(resource_changed::<Score>()),
+                    update_seed_text.run_if(resource_exists_and_changed::<LevelSeed>()),
+                    update_effect_timers,
                     update_mouse_coordinates.run_if(resource_changed::<MousePosition>()),
                     (update_measuring_tape_length, despawn_measuring_tape)
                         .distributive_run_if(any_with_component::<MeasuringTape>()),
@@ -71,7 +90,7 @@ impl MenuButton {
                     self.get_button_text(),
                     TextStyle {
                         font_size,
-                        color: Color::WHITE.into(),
+                        color: Color::WHITE,
                         ..Default::default()
                     },
                 ));
@@ -89,14 +108,29 @@ struct MouseCoordinates;
 struct MeasuringTape;
 
 #[derive(Component)]
-struct BallCoordinates;
+struct LifesCounter;
 
+/// Lists the timed effects in place and how long they have left.
 #[derive(Component)]
-struct LifesCounter;
+struct EffectTimers;
+
+#[derive(Component)]
+struct SeedText;
+
+#[derive(Component)]
+struct ScoreText;
 
 #[derive(Component)]
 struct Menu;
 
+/// Everything shown on top of the playfield while playing.
+#[derive(Component)]
+struct Hud;
+
+/// Text shown in the middle of the screen between levels.
+#[derive(Component)]
+struct Message;
+
 fn spawn_menu(mut commands: Commands) {
     commands
         .spawn((
@@ -131,10 +165,22 @@ fn spawn_menu(mut commands: Commands) {
         });
 }
 
-fn spawn_debug_ui(mut commands: Commands) {
+fn spawn_debug_ui(
+    mut commands: Commands,
+    bounces: Res<Bounces>,
+    lifes: Res<Lives>,
+    score: Res<Score>,
+    current_level: Res<CurrentLevel>,
+    game_config: Res<GameConfig>,
+    assets: Res<Assets<Config>>,
+) {
+    let Some(config) = assets.get(&game_config.config) else {
+        panic!("game config could not be loaded")
+    };
+
     commands.spawn((
         TextBundle::from_section(
-            "",
+            bounces.0.to_string(),
             TextStyle {
                 font_size: 30.0,
                 color: Color::WHITE,
@@ -149,11 +195,12 @@ fn spawn_debug_ui(mut commands: Commands) {
             ..default()
         }),
         BounceCounter,
+        Hud,
     ));
 
     commands.spawn((
         TextBundle::from_section(
-            "",
+            lifes_text(&lifes),
             TextStyle {
                 font_size: 20.0,
                 color: Color::WHITE,
@@ -168,17 +215,110 @@ fn spawn_debug_ui(mut commands: Commands) {
             ..default()
         }),
         LifesCounter,
+        Hud,
+    ));
+
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 20.0,
+                color: Color::WHITE,
+                ..Default::default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(30.0),
+            left: Val::Px(15.0),
+            ..default()
+        }),
+        EffectTimers,
+        Hud,
+    ));
+
+    commands.spawn((
+        TextBundle::from_section(
+            format!("Level {}/{}", current_level.0 + 1, level_count(config)),
+            TextStyle {
+                font_size: 20.0,
+                color: Color::WHITE,
+                ..Default::default()
+            },
+        )
+        .with_text_alignment(TextAlignment::Center)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(5.0),
+            right: Val::Px(15.0),
+            ..default()
+        }),
+        Hud,
+    ));
+
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 20.0,
+                color: Color::WHITE,
+                ..Default::default()
+            },
+        )
+        .with_text_alignment(TextAlignment::Center)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(30.0),
+            right: Val::Px(15.0),
+            ..default()
+        }),
+        SeedText,
+        Hud,
     ));
 
     commands
-        .spawn(NodeBundle {
-            style: Style {
-                width: Val::Percent(100.0),
-                justify_content: JustifyContent::Center,
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            Hud,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    score_text(&score),
+                    TextStyle {
+                        font_size: 30.0,
+                        color: Color::WHITE,
+                        ..Default::default()
+                    },
+                )
+                .with_text_alignment(TextAlignment::Center)
+                .with_style(Style {
+                    margin: UiRect::top(Val::Px(5.0)),
+                    ..Default::default()
+                }),
+                ScoreText,
+            ));
+        });
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    ..Default::default()
+                },
                 ..Default::default()
             },
-            ..Default::default()
-        })
+            Hud,
+        ))
         .with_children(|parent| {
             parent.spawn((
                 TextBundle::from_section(
@@ -278,5 +418,94 @@ fn update_lifes_counter(
     mut lifes_counter: Query<&mut Text, With<LifesCounter>>,
 ) {
     let mut text = lifes_counter.single_mut();
-    text.sections[0].value = format!("Lifes left: {}", lifes.0);
+    text.sections[0].value = lifes_text(&lifes);
+}
+
+fn update_score_text(score: Res<Score>, mut score_counter: Query<&mut Text, With<ScoreText>>) {
+    let Ok(mut text) = score_counter.get_single_mut() else {
+        return;
+    };
+    text.sections[0].value = score_text(&score);
+}
+
+fn update_seed_text(seed: Res<LevelSeed>, mut seed_text: Query<&mut Text, With<SeedText>>) {
+    let mut text = seed_text.single_mut();
+    text.sections[0].value = format!("Seed: {}", seed.0);
+}
+
+fn update_effect_timers(
+    effects: Query<&ActiveEffect>,
+    mut effect_timers: Query<&mut Text, With<EffectTimers>>,
+) {
+    let Ok(mut text) = effect_timers.get_single_mut() else {
+        return;
+    };
+    text.sections[0].value = effects
+        .iter()
+        .map(effect_text)
+        .collect::<Vec<_>>()
+        .join("\n");
+}
+
+fn effect_text(effect: &ActiveEffect) -> String {
+    match effect.stacks {
+        1 => format!("{}: {:.1}s", effect.kind.name(), effect.remaining()),
+        stacks => format!(
+            "{} x{stacks}: {:.1}s",
+            effect.kind.name(),
+            effect.remaining()
+        ),
+    }
+}
+
+fn score_text(score: &Score) -> String {
+    match score.combo {
+        0 | 1 => score.points.to_string(),
+        combo => format!("{} (combo {combo})", score.points),
+    }
+}
+
+fn lifes_text(lifes: &Lives) -> String {
+    format!("Lifes left: {}", lifes.0)
+}
+
+fn spawn_level_complete_message(commands: Commands) {
+    spawn_message(commands, "Level complete!\nPress Enter for the next level");
+}
+
+fn spawn_victory_message(commands: Commands) {
+    spawn_message(
+        commands,
+        "You cleared every level!\nPress Enter to return to the menu",
+    );
+}
+
+fn spawn_message(mut commands: Commands, message: &str) {
+    commands
+        .spawn((
+            Message,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    message,
+                    TextStyle {
+                        font_size: 40.0,
+                        color: Color::WHITE,
+                        ..Default::default()
+                    },
+                )
+                .with_text_alignment(TextAlignment::Center),
+            );
+        });
 }